@@ -0,0 +1,35 @@
+pragma solidity ^0.8.0;
+
+import "./base/GuardManager.sol";
+import "./interfaces/IERC165.sol";
+
+/// @dev Transaction guard for tests: rejects every Safe transaction to `blocked`
+contract GuardMock is Guard, IERC165 {
+    address public blocked;
+
+    constructor(address _blocked) {
+        blocked = _blocked;
+    }
+
+    function checkTransaction(
+        address to,
+        uint256,
+        bytes memory,
+        Enum.Operation,
+        uint256,
+        uint256,
+        uint256,
+        address,
+        address payable,
+        bytes memory,
+        address
+    ) external view override {
+        require(to != blocked, "GuardMock: blocked");
+    }
+
+    function checkAfterExecution(bytes32, bool) external override {}
+
+    function supportsInterface(bytes4 interfaceId) external pure override returns (bool) {
+        return interfaceId == type(Guard).interfaceId || interfaceId == type(IERC165).interfaceId;
+    }
+}
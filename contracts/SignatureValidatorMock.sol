@@ -0,0 +1,18 @@
+pragma solidity ^0.8.0;
+
+import "./interfaces/ISignatureValidator.sol";
+
+/// @dev EIP-1271 owner for tests: a signature is valid when it is the keccak256 of the signed data
+contract SignatureValidatorMock is ISignatureValidator {
+    function isValidSignature(bytes memory _data, bytes memory _signature)
+        public
+        pure
+        override
+        returns (bytes4)
+    {
+        if (keccak256(_signature) == keccak256(abi.encodePacked(keccak256(_data)))) {
+            return EIP1271_MAGIC_VALUE;
+        }
+        return 0x00000000;
+    }
+}
@@ -16,6 +16,13 @@
 
 use tideth::{config, safe::SafeClient};
 
+ethcontract::contract!(
+  "artifacts/contracts/GnosisSafeProxyFactory.sol/GnosisSafeProxyFactory.json"
+);
+ethcontract::contract!(
+  "artifacts/contracts/accessors/SimulateTxAccessor.sol/SimulateTxAccessor.json"
+);
+
 #[tokio::main]
 async fn main() {
   let net = std::env::var("NETWORK").expect("NETWORK REQUIRED");
@@ -23,14 +30,50 @@ async fn main() {
     .await
     .expect("failed to init web3");
 
-  if let Some(_) = conf.safe_factory_address {
-    panic!("already a SAFE factory address");
-  }
-  let mut safe = SafeClient::new(&web3, None).expect("derp");
-  let addy = safe
-    .deploy_factory(my_account.clone())
-    .await
-    .expect("couldnt deply singleton");
+  let safe_factory_address: String = if let Some(addy) = conf.safe_factory_address {
+    println!("SAFE factory address already exists in conf");
+    addy
+  } else {
+    let mut safe = SafeClient::new(&web3, None).expect("derp");
+    safe
+      .deploy_factory(my_account.clone())
+      .await
+      .expect("couldnt deply singleton")
+  };
+
+  // only needed to create the SAFE at a predicted address, see 3_safe
+  let proxy_factory_address: String = if let Some(addy) = conf.proxy_factory_address {
+    println!("proxy factory address already exists in conf");
+    addy
+  } else {
+    let proxy_factory = GnosisSafeProxyFactory::builder(&web3)
+      .from(my_account.clone())
+      .deploy()
+      .await
+      .expect("couldnt deploy proxy factory");
+    format!("{:?}", proxy_factory.address())
+  };
+
+  // only needed to dry run SAFE transactions with SafeClient::simulate
+  let simulate_accessor_address: String = if let Some(addy) = conf.simulate_accessor_address {
+    println!("simulate accessor address already exists in conf");
+    addy
+  } else {
+    let accessor = SimulateTxAccessor::builder(&web3)
+      .from(my_account.clone())
+      .deploy()
+      .await
+      .expect("couldnt deploy simulate accessor");
+    format!("{:?}", accessor.address())
+  };
 
-  println!("\"safe_factory_address\": {:?}", addy);
+  println!("===============");
+  println!("\"safe_factory_address\": {:?}", safe_factory_address);
+  println!("===============");
+  println!("\"proxy_factory_address\": {:?}", proxy_factory_address);
+  println!("===============");
+  println!(
+    "\"simulate_accessor_address\": {:?}",
+    simulate_accessor_address
+  );
 }
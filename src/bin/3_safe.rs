@@ -16,7 +16,10 @@
 
 use ethcontract::H160;
 use std::str::FromStr;
-use tideth::{config, safe::SafeClient};
+use tideth::{
+  config,
+  safe::{SafeClient, SafeSetup},
+};
 
 #[tokio::main]
 async fn main() {
@@ -34,10 +37,42 @@ async fn main() {
   let mut safe = SafeClient::new(&web3, None).expect("derp");
   let factory_address: H160 =
     H160::from_str(factory.as_str()).expect("couldnt parse factory address");
-  let addy = safe
-    .deploy_with_factory(my_account.clone(), factory_address)
-    .await
-    .expect("couldnt deply SAFE");
+  let addy = if let Some(proxy_factory) = conf.proxy_factory_address {
+    let proxy_factory_address: H160 =
+      H160::from_str(proxy_factory.as_str()).expect("couldnt parse proxy factory address");
+    let mut owners: Vec<H160> = conf
+      .owners
+      .iter()
+      .map(|a| H160::from_str(a).expect("couldnt parse H160"))
+      .collect();
+    owners.sort();
+    // the SAFE is created already set up, so 7_safe_owners is not needed
+    let setup = SafeSetup::new(owners, conf.threshold);
+    let salt_nonce = conf.salt_nonce.unwrap_or(0).into();
+    let predicted = safe
+      .predict_setup_address(proxy_factory_address, factory_address, &setup, salt_nonce)
+      .expect("couldnt predict SAFE address");
+    safe
+      .check_proxy_creation_code(proxy_factory_address)
+      .await
+      .expect("the predicted SAFE address doesnt hold for this proxy factory");
+    println!("predicted SAFE address: {:?}", predicted);
+    safe
+      .deploy_and_setup(
+        my_account.clone(),
+        proxy_factory_address,
+        factory_address,
+        &setup,
+        salt_nonce,
+      )
+      .await
+      .expect("couldnt deply SAFE")
+  } else {
+    safe
+      .deploy_with_factory(my_account.clone(), factory_address)
+      .await
+      .expect("couldnt deply SAFE")
+  };
 
   println!("\"safe_address\": {:?}", addy);
 }
@@ -14,7 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with tideth.  If not, see <http://www.gnu.org/licenses/>.
 
-use tideth::{config, router::RouterClient, safe::SafeClient};
+use tideth::{
+  config,
+  handover::{HandoverStep, RouterHandover},
+  router::RouterClient,
+  safe::SafeClient,
+};
 
 #[tokio::main]
 async fn main() {
@@ -24,30 +29,21 @@ async fn main() {
     .expect("failed to init web3");
 
   let router_address = conf.router_address.expect("no router address");
-  let mut router = RouterClient::new(&web3, Some(router_address.as_str())).expect("derp");
+  let router = RouterClient::new(&web3, Some(router_address.as_str())).expect("derp");
 
   let safe_address = conf.safe_address.expect("no safe address");
   let safe = SafeClient::new(&web3, Some(safe_address.as_str())).expect("derp2");
 
-  let pending_owner = router
-    .pending_owner()
+  // transfer router to safe, unless it's already pending or done
+  let handover = RouterHandover::new(&router, &safe);
+  let step = handover
+    .propose(my_account.clone())
     .await
-    .expect("couldnt get currenct owner");
-
-  // transfer router to safe
-  if pending_owner != safe.address() {
-    println!("pending owner is not SAFE, transfer now");
-    router
-      .transfer_ownership(my_account.clone(), safe.address())
-      .await
-      .expect("Couldnt transfer router ownership to safe");
-    println!("safe ownding owner of router!");
-  } else {
-    println!("safe already owns router");
-  }
-
-  let pending_owner2 = router.pending_owner().await.expect("couldnt call owner");
-  assert_eq!(pending_owner2, safe.address(), "safe should own router");
+    .expect("Couldnt transfer router ownership to safe");
 
-  println!("=> pending owner is SAFE!");
+  match step {
+    HandoverStep::Claim => println!("=> pending owner is SAFE!"),
+    HandoverStep::Done => println!("=> SAFE already owns router!"),
+    HandoverStep::Propose => panic!("safe should be the pending owner of router"),
+  }
 }
@@ -29,6 +29,9 @@ async fn main() {
   let safe_address = conf.safe_address.expect("no safe address");
   let safe = SafeClient::new(&web3, Some(safe_address.as_str())).expect("derp2");
 
+  let state = safe.state().await.expect("couldnt read safe state");
+  println!("Safe state at block {}: {:#?}", state.block_number, state);
+
   let owner = router.owner().await.expect("couldnt call owner");
   assert_eq!(owner, safe.address(), "safe should own router");
   println!("Safe is owner of Router");
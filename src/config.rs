@@ -29,6 +29,9 @@ pub struct Config {
   pub usdc_address: Option<String>,
   pub safe_address: Option<String>,
   pub safe_factory_address: Option<String>,
+  pub proxy_factory_address: Option<String>,
+  pub salt_nonce: Option<u64>,
+  pub simulate_accessor_address: Option<String>,
   pub router_address: Option<String>,
 }
 
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with tideth.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::Result;
+use crate::{safe::ExecResult, Result};
 use ethcontract::{prelude::*, transport::DynTransport, Address};
 
 ethcontract::contract!("https://tidefi-contracts.s3.eu-west-1.amazonaws.com/Tether.json");
@@ -28,3 +28,36 @@ pub async fn balance_of(
   let bal = tether.balance_of(address).call().await?;
   Ok(bal.as_u128())
 }
+
+/// How much `spender` may still transfer from `owner`
+pub async fn allowance(
+  web3: &Web3<DynTransport>,
+  asset_address: Address,
+  owner: Address,
+  spender: Address,
+) -> Result<U256> {
+  let tether = Tether::at(web3, asset_address);
+  Ok(tether.allowance(owner, spender).call().await?)
+}
+
+/// Set the allowance of `spender` over `from_account`'s tokens to `amount`
+pub async fn approve(
+  web3: &Web3<DynTransport>,
+  from_account: Account<DynTransport>,
+  asset_address: Address,
+  spender: Address,
+  amount: U256,
+) -> Result<ExecResult> {
+  let tether = Tether::at(web3, asset_address);
+  let nonce = web3
+    .eth()
+    .transaction_count(from_account.address(), None)
+    .await?;
+  let tx_result = tether
+    .approve(spender, amount)
+    .from(from_account)
+    .nonce(nonce)
+    .send()
+    .await?;
+  Ok(tx_result.into())
+}
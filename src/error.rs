@@ -30,6 +30,18 @@ pub enum Error {
   CHexError(#[from] rustc_hex::FromHexError),
   #[error("ConfirmationTimeout: {0}")]
   ConfirmationTimeout(String),
+  #[error("invalid signature: {0}")]
+  InvalidSignature(String),
+  #[error("threshold not met: {0} of {1} signatures")]
+  ThresholdNotMet(usize, u64),
+  /// The SafeTx hash and the hash of the transaction that executed it
+  #[error("safe transaction {0:?} failed in {1:?}, its nonce is consumed")]
+  SafeExecutionFailure(ethcontract::H256, ethcontract::H256),
+  /// The module and the hash of the transaction it sent
+  #[error("module {0:?} transaction {1:?} failed in the safe")]
+  ModuleExecutionFailure(ethcontract::Address, ethcontract::H256),
+  #[error("unsupported safe version {0}, expected 1.3.0 or 1.4.x")]
+  UnsupportedSafeVersion(String),
   #[error("error: {0}")]
   Other(String),
 }
@@ -0,0 +1,189 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Handing the Router over to the custody Safe: the current owner proposes the Safe with
+//! `transferOwnership`, the Safe's owners sign a `claimOwnership` SafeTx, and anyone executes
+//! it. Every step starts by reading the chain, so an interrupted handover resumes where it
+//! stopped. The claim is rebuilt identically until the Safe's nonce moves, so signatures
+//! collected before an interruption stay valid.
+
+use crate::{
+  error::Error,
+  router::RouterClient,
+  safe::{ExecResult, SafeClient, SafeSignatures, SafeTransaction},
+  utils, Result,
+};
+use ethcontract::{prelude::*, transport::DynTransport};
+
+/// What the handover is waiting for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HandoverStep {
+  /// The Safe isn't the pending owner: the current owner must call `transferOwnership`
+  Propose,
+  /// The Safe is the pending owner: its owners must sign and execute `claimOwnership`
+  Claim,
+  /// The Safe owns the router
+  Done,
+}
+
+#[derive(Clone)]
+pub struct RouterHandover {
+  router: RouterClient,
+  safe: SafeClient,
+}
+
+impl RouterHandover {
+  pub fn new(router: &RouterClient, safe: &SafeClient) -> Self {
+    Self {
+      router: router.clone(),
+      safe: safe.clone(),
+    }
+  }
+
+  /// Read the router's owner and pending owner to find the next step
+  pub async fn step(&self) -> Result<HandoverStep> {
+    let status = self.router.handover_status().await?;
+    Ok(if status.owner == self.safe.address() {
+      HandoverStep::Done
+    } else if status.pending_owner == self.safe.address() {
+      HandoverStep::Claim
+    } else {
+      HandoverStep::Propose
+    })
+  }
+
+  /// Propose the Safe as the router's next owner from `owner`, unless it already is
+  pub async fn propose(&self, owner: Account<DynTransport>) -> Result<HandoverStep> {
+    let step = self.step().await?;
+    if step != HandoverStep::Propose {
+      return Ok(step);
+    }
+    let current = self.router.owner().await?;
+    if current != owner.address() {
+      return Err(Error::Other(format!(
+        "{:?} can't propose the safe, the router is owned by {:?}",
+        owner.address(),
+        current
+      )));
+    }
+    let mut router = self.router.clone();
+    router
+      .transfer_ownership(owner, self.safe.address())
+      .await?;
+    self.step().await
+  }
+
+  /// The `claimOwnership` SafeTx at the Safe's current nonce, for the owners to sign
+  pub async fn claim_transaction(&self) -> Result<SafeTransaction> {
+    let step = self.step().await?;
+    if step != HandoverStep::Claim {
+      return Err(Error::Other(format!(
+        "the router can't be claimed by the safe at step {:?}",
+        step
+      )));
+    }
+    Ok(
+      self
+        .safe
+        .new_transaction(self.router.address())
+        .await?
+        .data(self.router.claim_ownership_data()?),
+    )
+  }
+
+  /// A signature set for `tx`, with every owner who already approved it on chain
+  pub async fn claim_signatures(&self, tx: &SafeTransaction) -> Result<SafeSignatures> {
+    let mut signatures = self.safe.signatures(tx).await?;
+    self.safe.add_approvals(&mut signatures).await?;
+    Ok(signatures)
+  }
+
+  /// Execute the signed claim from `executor`, then check the Safe owns the router
+  pub async fn execute(
+    &self,
+    executor: Account<DynTransport>,
+    tx: &SafeTransaction,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let result = self.safe.exec_signed(executor, tx, signatures, gas).await?;
+    self.verify().await?;
+    Ok(result)
+  }
+
+  /// Check the Safe owns the router and no other handover is pending
+  pub async fn verify(&self) -> Result<()> {
+    let status = self.router.handover_status().await?;
+    if status.owner != self.safe.address() {
+      return Err(Error::Other(format!(
+        "router is owned by {:?}, not the safe {:?}",
+        status.owner,
+        self.safe.address()
+      )));
+    }
+    if status.pending_owner != utils::zero_address() {
+      return Err(Error::Other(format!(
+        "router has a pending handover to {:?}",
+        status.pending_owner
+      )));
+    }
+    Ok(())
+  }
+
+  /// Take the handover as far as it can go: propose from `owner` if needed, then execute
+  /// the claim from `executor` once `signatures` (65 byte ECDSA signatures over the claim),
+  /// on-chain approvals and `executor` itself, if an owner, meet the threshold.
+  /// Signatures for an older claim are skipped. Returns the step it stopped at.
+  pub async fn run(
+    &self,
+    owner: Option<Account<DynTransport>>,
+    executor: Account<DynTransport>,
+    signatures: &[Vec<u8>],
+    gas: Option<u128>,
+  ) -> Result<HandoverStep> {
+    let mut step = self.step().await?;
+    if step == HandoverStep::Propose {
+      step = match owner {
+        Some(o) => self.propose(o).await?,
+        None => return Ok(step),
+      };
+    }
+    if step != HandoverStep::Claim {
+      return Ok(step);
+    }
+
+    let tx = self.claim_transaction().await?;
+    let mut set = self.claim_signatures(&tx).await?;
+    for signature in signatures {
+      if let Err(e) = set.add(signature) {
+        log::warn!("skipping claim signature: {}", e);
+      }
+    }
+    let executor_address = executor.address();
+    if !set.is_complete() && !set.signers().contains(&executor_address) {
+      // the executor's own `v = 1` entry needs no approval
+      if let Err(e) = set.add_approved_hash(executor_address) {
+        log::debug!("executor can't approve the claim: {}", e);
+      }
+    }
+    if !set.is_complete() {
+      log::info!("claim has {} of {} signatures", set.len(), set.threshold());
+      return Ok(HandoverStep::Claim);
+    }
+    self.execute(executor, &tx, &set, gas).await?;
+    Ok(HandoverStep::Done)
+  }
+}
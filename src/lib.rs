@@ -17,6 +17,7 @@
 pub mod config;
 pub mod erc20;
 pub mod error;
+pub mod handover;
 pub mod router;
 pub mod safe;
 pub mod utils;
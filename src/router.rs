@@ -14,18 +14,27 @@
 // You should have received a copy of the GNU General Public License
 // along with tideth.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::{error::Error, utils, Result};
+use crate::{error::Error, safe::MultiSendCall, utils, Result};
 use ethcontract::{
   prelude::*,
   tokens::Tokenize,
   transaction::TransactionResult,
   transport::DynTransport,
-  web3::ethabi::{param_type::ParamType, Function, Param},
+  web3::{
+    ethabi::{param_type::ParamType, Function, Param},
+    types::BlockId,
+  },
 };
 use futures::{channel::mpsc, sink::SinkExt, stream::StreamExt};
 
 ethcontract::contract!("https://tidefi-contracts.s3.eu-west-1.amazonaws.com/Router.json");
 
+mod admin;
+mod deposit;
+
+pub use admin::{AdminCall, RouterAction, RouterOwner};
+pub use deposit::{ApproveMode, DepositReceipt};
+
 #[derive(Debug, Clone)]
 pub struct DepositEvent {
   pub amount: u128,
@@ -46,6 +55,124 @@ pub struct WithdrawEvent {
   pub confirmations: u64,
 }
 
+/// An `Accepted` (`accepted == true`) or `Removed` event
+#[derive(Debug, Clone)]
+pub struct AcceptlistEvent {
+  pub asset: H160,
+  pub accepted: bool,
+  pub tx_hash: Vec<u8>,
+  pub log_index: usize,
+  pub blockheight: u64,
+  pub confirmations: u64,
+}
+
+/// An asset's acceptlist state, replayed from its `Accepted` and `Removed` events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptlistEntry {
+  pub asset: H160,
+  pub accepted: bool,
+  /// Block of the latest `Accepted`, if any
+  pub added_at: Option<u64>,
+  /// Block of the latest `Removed`, if any
+  pub removed_at: Option<u64>,
+}
+
+/// Replay acceptlist events, oldest first, into one entry per asset in the order
+/// the assets first appeared. The last event for an asset decides whether it is accepted.
+pub fn replay_acceptlist(events: &[AcceptlistEvent]) -> Vec<AcceptlistEntry> {
+  let mut entries: Vec<AcceptlistEntry> = vec![];
+  for e in events {
+    let index = match entries.iter().position(|a| a.asset == e.asset) {
+      Some(i) => i,
+      None => {
+        entries.push(AcceptlistEntry {
+          asset: e.asset,
+          accepted: false,
+          added_at: None,
+          removed_at: None,
+        });
+        entries.len() - 1
+      }
+    };
+    let entry = &mut entries[index];
+    entry.accepted = e.accepted;
+    if e.accepted {
+      entry.added_at = Some(e.blockheight);
+    } else {
+      entry.removed_at = Some(e.blockheight);
+    }
+  }
+  entries
+}
+
+/// The two steps of a `SafeOwnable` handover
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnershipChange {
+  /// `OwnershipTransferProposed`, emitted by `transferOwnership`
+  Proposed,
+  /// `OwnershipTransferred`, emitted by the constructor and `claimOwnership`
+  Transferred,
+}
+
+#[derive(Debug, Clone)]
+pub struct OwnershipEvent {
+  pub change: OwnershipChange,
+  pub old_owner: H160,
+  pub new_owner: H160,
+  pub tx_hash: Vec<u8>,
+  pub log_index: usize,
+  pub blockheight: u64,
+  pub confirmations: u64,
+}
+
+/// A span of blocks during which `owner` owned the router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipPeriod {
+  pub owner: H160,
+  pub from_block: u64,
+  /// The block ownership was claimed by the next owner, `None` for the current owner
+  pub until_block: Option<u64>,
+}
+
+/// Where a two-step handover stands
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandoverStatus {
+  pub block_number: u64,
+  pub owner: H160,
+  /// Zero if no handover is pending
+  pub pending_owner: H160,
+  /// The owner who proposed the pending handover
+  pub proposed_by: Option<H160>,
+  /// The block the pending handover was proposed in
+  pub proposed_at: Option<u64>,
+}
+
+impl HandoverStatus {
+  pub fn is_pending(&self) -> bool {
+    self.pending_owner != utils::zero_address()
+  }
+}
+
+/// The owners of the router, oldest first, from its ownership events.
+/// Only `Transferred` events change the owner, proposals are ignored.
+pub fn ownership_timeline(events: &[OwnershipEvent]) -> Vec<OwnershipPeriod> {
+  let mut periods: Vec<OwnershipPeriod> = vec![];
+  for e in events
+    .iter()
+    .filter(|e| e.change == OwnershipChange::Transferred)
+  {
+    if let Some(last) = periods.last_mut() {
+      last.until_block = Some(e.blockheight);
+    }
+    periods.push(OwnershipPeriod {
+      owner: e.new_owner,
+      from_block: e.blockheight,
+      until_block: None,
+    });
+  }
+  periods
+}
+
 pub type Sender<T> = mpsc::UnboundedSender<T>;
 pub type Receiver<T> = mpsc::UnboundedReceiver<T>;
 
@@ -145,6 +272,59 @@ impl RouterClient {
     Ok(router.pending_owner().call().await?)
   }
 
+  /// Every `OwnershipTransferProposed` and `OwnershipTransferred` since `since` (or ever),
+  /// oldest first
+  pub async fn get_ownership_events(&self, since: Option<u64>) -> Result<Vec<OwnershipEvent>> {
+    let router = Router::at(&self.web3, self.address);
+    let bn: BlockNumber = match since {
+      Some(s) => s.into(),
+      None => BlockNumber::Earliest,
+    };
+    let events = router.all_events().from_block(bn).query().await?;
+    Ok(self.events_to_ownership(events).await)
+  }
+
+  /// Every owner the router has had since it was deployed
+  pub async fn ownership_history(&self) -> Result<Vec<OwnershipPeriod>> {
+    let events = self.get_ownership_events(None).await?;
+    Ok(ownership_timeline(&events))
+  }
+
+  /// Whether a handover is pending, and who proposed it when, read at a single block
+  pub async fn handover_status(&self) -> Result<HandoverStatus> {
+    let router = Router::at(&self.web3, self.address);
+    let block_number = self.block_number().await?;
+    let block = BlockId::Number(block_number.into());
+    let owner = router.owner().block(block).call().await?;
+    let pending_owner = router.pending_owner().block(block).call().await?;
+    let mut status = HandoverStatus {
+      block_number,
+      owner,
+      pending_owner,
+      proposed_by: None,
+      proposed_at: None,
+    };
+    if status.is_pending() {
+      let events = router
+        .all_events()
+        .from_block(BlockNumber::Earliest)
+        .to_block(block_number.into())
+        .query()
+        .await?;
+      let proposal = self
+        .events_to_ownership(events)
+        .await
+        .into_iter()
+        .rev()
+        .find(|e| e.change == OwnershipChange::Proposed && e.new_owner == pending_owner);
+      if let Some(p) = proposal {
+        status.proposed_by = Some(p.old_owner);
+        status.proposed_at = Some(p.blockheight);
+      }
+    }
+    Ok(status)
+  }
+
   fn _make_data(&self, to: H160, asset: H160, amount: u128) -> Result<Vec<u8>> {
     #[allow(deprecated)]
     let withdrawal = Function {
@@ -247,6 +427,27 @@ impl RouterClient {
     Ok(data)
   }
 
+  fn _make_transfer_ownership_data(&self, new_owner: Address) -> Result<Vec<u8>> {
+    #[allow(deprecated)]
+    let transfer = Function {
+      name: "transferOwnership".to_owned(),
+      inputs: vec![Param {
+        name: "".to_owned(),
+        kind: ParamType::Address,
+        internal_type: None,
+      }],
+      outputs: vec![],
+      constant: None,
+      state_mutability: Default::default(),
+    };
+    let tx = transfer.encode_input(&vec![new_owner.into_token()]);
+    if let Err(e) = tx {
+      return Err(Error::Other(e.to_string()));
+    }
+    let data = tx.unwrap();
+    Ok(data)
+  }
+
   pub fn eth_withdrawal_data(&self, to: H160, amount: u128) -> Result<Vec<u8>> {
     let eth: Address = utils::zero_address();
     self._make_data(to, eth, amount)
@@ -256,6 +457,23 @@ impl RouterClient {
     self._make_data(to, asset, amount)
   }
 
+  /// An ETH withdrawal as one call of a MultiSend batch; the Safe sends `amount` along
+  pub fn eth_withdrawal_call(&self, to: H160, amount: u128) -> Result<MultiSendCall> {
+    let data = self.eth_withdrawal_data(to, amount)?;
+    Ok(MultiSendCall::call(self.address, amount.into(), data))
+  }
+
+  /// An ERC20 withdrawal as one call of a MultiSend batch
+  pub fn erc20_withdrawal_call(
+    &self,
+    to: H160,
+    asset: H160,
+    amount: u128,
+  ) -> Result<MultiSendCall> {
+    let data = self.erc20_withdrawal_data(to, asset, amount)?;
+    Ok(MultiSendCall::call(self.address, U256::zero(), data))
+  }
+
   pub fn claim_ownership_data(&self) -> Result<Vec<u8>> {
     self._make_claim_data()
   }
@@ -265,7 +483,11 @@ impl RouterClient {
   }
 
   pub fn remove_token_data(&self, asset: Address) -> Result<Vec<u8>> {
-    self._make_accept_data(asset)
+    self._make_remove_data(asset)
+  }
+
+  pub fn transfer_ownership_data(&self, new_owner: Address) -> Result<Vec<u8>> {
+    self._make_transfer_ownership_data(new_owner)
   }
 
   pub async fn deposit(
@@ -466,6 +688,42 @@ impl RouterClient {
     Ok(self.events_to_withdrawals(events).await)
   }
 
+  /// Every `Accepted` and `Removed` event since `since` (or ever), oldest first
+  pub async fn get_acceptlist_events(&self, since: Option<u64>) -> Result<Vec<AcceptlistEvent>> {
+    let router = Router::at(&self.web3, self.address);
+    let bn: BlockNumber = match since {
+      Some(s) => s.into(),
+      None => BlockNumber::Earliest,
+    };
+    let events = router.all_events().from_block(bn).query().await?;
+    Ok(self.events_to_acceptlist(events).await)
+  }
+
+  /// The assets accepted as of `at_block` (or the latest block), each with the block
+  /// it was last accepted in
+  pub async fn accepted_assets(&self, at_block: Option<u64>) -> Result<Vec<AcceptlistEntry>> {
+    let history = self.acceptlist_history(at_block).await?;
+    Ok(history.into_iter().filter(|e| e.accepted).collect())
+  }
+
+  /// Every asset the acceptlist has seen as of `at_block` (or the latest block), replayed
+  /// from the router's history since `isAccepted` can't be enumerated. Removed assets are
+  /// included with `accepted == false`.
+  pub async fn acceptlist_history(&self, at_block: Option<u64>) -> Result<Vec<AcceptlistEntry>> {
+    let router = Router::at(&self.web3, self.address);
+    let to: BlockNumber = match at_block {
+      Some(b) => b.into(),
+      None => BlockNumber::Latest,
+    };
+    let events = router
+      .all_events()
+      .from_block(BlockNumber::Earliest)
+      .to_block(to)
+      .query()
+      .await?;
+    Ok(replay_acceptlist(&self.events_to_acceptlist(events).await))
+  }
+
   pub async fn subscribe_events(&self, mut tx: Sender<DepositEvent>) {
     let router = Router::at(&self.web3, self.address);
     let mut deposits = router
@@ -565,4 +823,55 @@ impl RouterClient {
     });
     deps
   }
+
+  async fn events_to_acceptlist(&self, events: Vec<Event<router::Event>>) -> Vec<AcceptlistEvent> {
+    let bn = self.block_number().await.ok();
+    events
+      .iter()
+      .filter_map(|e| {
+        let (asset, accepted) = match &e.data {
+          router::Event::Accepted(a) => (a.asset, true),
+          router::Event::Removed(r) => (r.asset, false),
+          _ => return None,
+        };
+        let meta = e.meta.as_ref()?;
+        Some(AcceptlistEvent {
+          asset,
+          accepted,
+          tx_hash: meta.transaction_hash.as_bytes().to_vec(),
+          log_index: meta.log_index,
+          blockheight: meta.block_number,
+          confirmations: bn.map_or(0, |n| n.saturating_sub(meta.block_number)),
+        })
+      })
+      .collect()
+  }
+
+  async fn events_to_ownership(&self, events: Vec<Event<router::Event>>) -> Vec<OwnershipEvent> {
+    let bn = self.block_number().await.ok();
+    events
+      .iter()
+      .filter_map(|e| {
+        let (change, old_owner, new_owner) = match &e.data {
+          router::Event::OwnershipTransferProposed(p) => {
+            (OwnershipChange::Proposed, p.old_owner, p.new_owner)
+          }
+          router::Event::OwnershipTransferred(t) => {
+            (OwnershipChange::Transferred, t.old_owner, t.new_owner)
+          }
+          _ => return None,
+        };
+        let meta = e.meta.as_ref()?;
+        Some(OwnershipEvent {
+          change,
+          old_owner,
+          new_owner,
+          tx_hash: meta.transaction_hash.as_bytes().to_vec(),
+          log_index: meta.log_index,
+          blockheight: meta.block_number,
+          confirmations: bn.map_or(0, |n| n.saturating_sub(meta.block_number)),
+        })
+      })
+      .collect()
+  }
 }
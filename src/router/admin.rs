@@ -0,0 +1,220 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Owner-only Router calls. While an account owns the router they are sent directly; once
+//! the Safe owns it they become SafeTransactions for the Safe's owners to sign, since a
+//! direct call would revert with S01.
+
+use super::RouterClient;
+use crate::{
+  error::Error,
+  safe::{ExecResult, SafeClient, SafeTransaction},
+  utils, Result,
+};
+use ethcontract::{
+  prelude::*, transaction::TransactionBuilder, transport::DynTransport,
+  web3::types::Bytes as Web3Bytes,
+};
+
+/// An owner-only Router function and its arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RouterAction {
+  AcceptToken(Address),
+  RemoveToken(Address),
+  TransferOwnership(Address),
+  /// `asset` is zero for ETH, which the owner sends along with the call
+  Withdraw {
+    to: Address,
+    asset: Address,
+    amount: u128,
+  },
+}
+
+impl RouterAction {
+  /// The calldata for `router`
+  pub fn data(&self, router: &RouterClient) -> Result<Vec<u8>> {
+    match self {
+      RouterAction::AcceptToken(asset) => router.accept_token_data(*asset),
+      RouterAction::RemoveToken(asset) => router.remove_token_data(*asset),
+      RouterAction::TransferOwnership(owner) => router.transfer_ownership_data(*owner),
+      RouterAction::Withdraw { to, asset, amount } if *asset == utils::zero_address() => {
+        router.eth_withdrawal_data(*to, *amount)
+      }
+      RouterAction::Withdraw { to, asset, amount } => {
+        router.erc20_withdrawal_data(*to, *asset, *amount)
+      }
+    }
+  }
+
+  /// The ETH sent with the call: the amount of an ETH withdrawal, zero otherwise
+  pub fn value(&self) -> U256 {
+    match self {
+      RouterAction::Withdraw { asset, amount, .. } if *asset == utils::zero_address() => {
+        (*amount).into()
+      }
+      _ => U256::zero(),
+    }
+  }
+}
+
+/// What kind of account owns the router
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RouterOwner {
+  /// An externally owned account, which sends owner calls itself
+  Account(Address),
+  /// A supported Safe, whose owners sign owner calls
+  Safe(Address),
+  /// Any other contract, which tideth can't act for
+  Contract(Address),
+}
+
+/// The result of an owner call
+#[derive(Clone)]
+pub enum AdminCall {
+  /// The owner account sent it
+  Sent(ExecResult),
+  /// The Safe owns the router: this transaction needs the owners' signatures
+  Proposed(SafeTransaction),
+}
+
+impl RouterClient {
+  /// Find out whether the router's owner is an account, a Safe or another contract
+  pub async fn owner_kind(&self) -> Result<RouterOwner> {
+    let owner = self.owner().await?;
+    let code = self.web3.eth().code(owner, None).await?;
+    if code.0.is_empty() {
+      return Ok(RouterOwner::Account(owner));
+    }
+    let mut safe = SafeClient::new(&self.web3, None)?;
+    safe.set_address(owner);
+    match safe.detect_version().await {
+      Ok(_) => Ok(RouterOwner::Safe(owner)),
+      Err(e) => {
+        log::debug!("router owner {:?} is not a safe: {}", owner, e);
+        Ok(RouterOwner::Contract(owner))
+      }
+    }
+  }
+
+  /// `action` as a SafeTransaction to the router at `safe`'s current nonce.
+  /// Errors if `safe` doesn't own the router, as the call would revert.
+  pub async fn action_tx(
+    &self,
+    safe: &SafeClient,
+    action: &RouterAction,
+  ) -> Result<SafeTransaction> {
+    let owner = self.owner().await?;
+    if owner != safe.address() {
+      return Err(Error::Other(format!(
+        "router is owned by {:?}, not the safe {:?}",
+        owner,
+        safe.address()
+      )));
+    }
+    Ok(
+      safe
+        .new_transaction(self.address)
+        .await?
+        .value(action.value())
+        .data(action.data(self)?),
+    )
+  }
+
+  pub async fn accept_token_tx(
+    &self,
+    safe: &SafeClient,
+    asset: Address,
+  ) -> Result<SafeTransaction> {
+    self
+      .action_tx(safe, &RouterAction::AcceptToken(asset))
+      .await
+  }
+
+  pub async fn remove_token_tx(
+    &self,
+    safe: &SafeClient,
+    asset: Address,
+  ) -> Result<SafeTransaction> {
+    self
+      .action_tx(safe, &RouterAction::RemoveToken(asset))
+      .await
+  }
+
+  pub async fn transfer_ownership_tx(
+    &self,
+    safe: &SafeClient,
+    new_owner: Address,
+  ) -> Result<SafeTransaction> {
+    self
+      .action_tx(safe, &RouterAction::TransferOwnership(new_owner))
+      .await
+  }
+
+  pub async fn withdraw_tx(
+    &self,
+    safe: &SafeClient,
+    to: Address,
+    asset: Address,
+    amount: u128,
+  ) -> Result<SafeTransaction> {
+    self
+      .action_tx(safe, &RouterAction::Withdraw { to, asset, amount })
+      .await
+  }
+
+  /// Perform `action` the way the current owner can: sent from `from_account` if it
+  /// owns the router, or proposed as a SafeTransaction if a Safe does
+  pub async fn admin(
+    &self,
+    from_account: Account<DynTransport>,
+    action: &RouterAction,
+  ) -> Result<AdminCall> {
+    match self.owner_kind().await? {
+      RouterOwner::Account(owner) => {
+        if owner != from_account.address() {
+          return Err(Error::Other(format!(
+            "router is owned by {:?}, not {:?}",
+            owner,
+            from_account.address()
+          )));
+        }
+        let nonce = self
+          .web3
+          .eth()
+          .transaction_count(from_account.address(), None)
+          .await?;
+        let result = TransactionBuilder::new(self.web3.clone())
+          .from(from_account)
+          .nonce(nonce)
+          .to(self.address)
+          .value(action.value())
+          .data(Web3Bytes(action.data(self)?))
+          .send()
+          .await?;
+        Ok(AdminCall::Sent(result.into()))
+      }
+      RouterOwner::Safe(owner) => {
+        let mut safe = SafeClient::new(&self.web3, None)?;
+        safe.set_address(owner);
+        Ok(AdminCall::Proposed(self.action_tx(&safe, action).await?))
+      }
+      RouterOwner::Contract(owner) => Err(Error::Other(format!(
+        "router is owned by {:?}, which is not a supported safe",
+        owner
+      ))),
+    }
+  }
+}
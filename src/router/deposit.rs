@@ -0,0 +1,149 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ERC20 deposits that check the acceptlist, balance and allowance before sending anything,
+//! instead of reverting inside `safeTransferFrom` with no hint why.
+
+use super::RouterClient;
+use crate::{erc20, error::Error, safe::ExecResult, utils, Result};
+use ethcontract::{prelude::*, transport::DynTransport};
+
+/// What to do when the router's allowance is below the deposit amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApproveMode {
+  /// Fail before depositing
+  Never,
+  /// Approve exactly the deposit amount first
+  Approve,
+  /// For tokens like USDT that refuse to change a non-zero allowance: reset it to zero,
+  /// approve exactly the deposit amount, and reset whatever of that approval is left
+  /// after the deposit, even if it failed. A sufficient allowance is left alone.
+  ApproveAndReset,
+}
+
+/// Every transaction an ERC20 deposit took
+#[derive(Clone)]
+pub struct DepositReceipt {
+  /// The `approve` calls, in the order they were sent
+  pub approvals: Vec<ExecResult>,
+  pub tx_hash: Vec<u8>,
+  /// Gas used by the approvals and the deposit together
+  pub gas_used: u128,
+}
+
+impl RouterClient {
+  /// Deposit `amount` of the ERC20 `asset` for the Tidechain `account`, approving the
+  /// router first according to `mode`
+  pub async fn deposit_erc20(
+    &self,
+    from_account: Account<DynTransport>,
+    account: Vec<u8>,
+    asset: Address,
+    amount: U256,
+    mode: ApproveMode,
+  ) -> Result<DepositReceipt> {
+    if asset == utils::zero_address() {
+      return Err(Error::Other(
+        "ETH deposits go through deposit, not deposit_erc20".to_string(),
+      ));
+    }
+    if !self.is_accepted(asset).await? {
+      return Err(Error::Other(format!(
+        "{:?} is not accepted by the router",
+        asset
+      )));
+    }
+    let from = from_account.address();
+    let balance = erc20::balance_of(&self.web3, asset, from).await?;
+    if U256::from(balance) < amount {
+      return Err(Error::Other(format!(
+        "balance {} of {:?} is below the deposit of {}",
+        balance, asset, amount
+      )));
+    }
+
+    let mut approvals = vec![];
+    let allowance = erc20::allowance(&self.web3, asset, from, self.address).await?;
+    // only an allowance this call granted is ever reset, never one the caller set up
+    let approved = allowance < amount;
+    if approved {
+      match mode {
+        ApproveMode::Never => {
+          return Err(Error::Other(format!(
+            "router allowance {} is below the deposit of {}, approve it first",
+            allowance, amount
+          )))
+        }
+        ApproveMode::Approve => {}
+        ApproveMode::ApproveAndReset => {
+          if !allowance.is_zero() {
+            approvals.push(
+              self
+                .approve(from_account.clone(), asset, U256::zero())
+                .await?,
+            );
+          }
+        }
+      }
+      approvals.push(self.approve(from_account.clone(), asset, amount).await?);
+    }
+
+    let deposit = self
+      .deposit(from_account.clone(), account, asset, amount, None)
+      .await;
+
+    if approved && mode == ApproveMode::ApproveAndReset {
+      match self.reset_allowance(from_account, asset).await {
+        Ok(Some(reset)) => approvals.push(reset),
+        Ok(None) => {}
+        // the deposit error is the one worth returning
+        Err(e) if deposit.is_err() => log::warn!("couldnt reset the router allowance: {}", e),
+        Err(e) => return Err(e),
+      }
+    }
+    let (tx_hash, deposit_gas) = deposit?;
+
+    let gas_used = approvals.iter().map(|a| a.gas_used).sum::<u128>() + deposit_gas;
+    Ok(DepositReceipt {
+      approvals,
+      tx_hash,
+      gas_used,
+    })
+  }
+
+  /// Set the router's allowance back to zero if any of it is left
+  async fn reset_allowance(
+    &self,
+    from_account: Account<DynTransport>,
+    asset: Address,
+  ) -> Result<Option<ExecResult>> {
+    let from = from_account.address();
+    let left = erc20::allowance(&self.web3, asset, from, self.address).await?;
+    if left.is_zero() {
+      return Ok(None);
+    }
+    Ok(Some(self.approve(from_account, asset, U256::zero()).await?))
+  }
+
+  async fn approve(
+    &self,
+    from_account: Account<DynTransport>,
+    asset: Address,
+    amount: U256,
+  ) -> Result<ExecResult> {
+    erc20::approve(&self.web3, from_account, asset, self.address, amount).await
+  }
+}
@@ -21,17 +21,81 @@ use ethcontract::{
   tokens::Tokenize,
   transaction::TransactionResult,
   transport::DynTransport,
-  web3::ethabi::{param_type::ParamType, Function, Param},
+  web3::{
+    ethabi::{param_type::ParamType, Function, Param, Token},
+    types::{Bytes as Web3Bytes, CallRequest},
+  },
   Bytes,
 };
+use std::sync::OnceLock;
 
 ethcontract::contract!("https://tidefi-contracts.s3.eu-west-1.amazonaws.com/GnosisSafe.json");
 ethcontract::contract!("https://tidefi-contracts.s3.eu-west-1.amazonaws.com/GnosisSafeProxy.json");
 
+pub mod eip712;
+mod events;
+mod factory;
+mod gas;
+mod guard;
+mod modules;
+mod multisend;
+mod owners;
+mod received;
+mod setup;
+mod signatures;
+mod simulate;
+mod state;
+mod transaction;
+mod version;
+
+pub use events::{
+  decode_log, execution_result, SafeEvent, SafeEventLog, EXECUTION_FAILURE_TOPIC,
+  EXECUTION_SUCCESS_TOPIC,
+};
+pub use factory::{
+  bundled_proxy_creation_code, create_proxy_with_nonce_data, predict_proxy_address,
+  predict_proxy_address_with_code, proxy_created, proxy_salt, PROXY_CREATION_TOPIC,
+};
+pub use gas::{data_gas, required_gas_left, GasEstimate};
+pub use guard::{set_guard_data, ERC165_INTERFACE_ID, GUARD_INTERFACE_ID};
+pub use modules::{disable_module_data, enable_module_data, prev_module, ModuleClient};
+pub use multisend::{
+  decode_multi_send, decode_multi_send_data, encode_multi_send, multi_send_data, MultiSendCall,
+};
+pub use owners::{
+  add_owner_with_threshold_data, change_threshold_data, prev_owner, remove_owner_data,
+  swap_owner_data,
+};
+pub use received::{correlate_deposits, ReceivedEvent};
+pub use setup::SafeSetup;
+pub use signatures::{
+  approved_hash_signature, check_signatures, check_unpacked_signatures, contract_signature,
+  contract_signature_data, eth_sign_hash, recover, recover_eth_sign, sign_eth_sign, sign_hash,
+  unpack_signatures, PackedSignature, SafeSignature, SafeSignatures, SignatureCheck,
+  SignatureReport, SignatureStatus, SignatureType,
+};
+pub use simulate::{decode_simulation, simulate_data, Simulation};
+pub use state::{SafeState, FALLBACK_HANDLER_STORAGE_SLOT, GUARD_STORAGE_SLOT};
+pub use transaction::{Operation, SafeTransaction};
+pub use version::SafeVersion;
+
+/// `SENTINEL_OWNERS` / `SENTINEL_MODULES`, the head of the Safe's linked lists
+pub const SENTINEL_ADDRESS: Address =
+  H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+/// `ISignatureValidatorConstants.EIP1271_MAGIC_VALUE`, the selector of the legacy
+/// `isValidSignature(bytes,bytes)`
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x20, 0xc1, 0x3b, 0x0b];
+
 #[derive(Clone)]
 pub struct SafeClient {
   web3: Web3<DynTransport>,
   address: Address,
+  chain_id: Option<u64>,
+  safe_version: Option<SafeVersion>,
+  detected_version: OnceLock<SafeVersion>,
+  simulate_accessor: Option<Address>,
+  preflight: bool,
 }
 
 #[derive(Clone)]
@@ -39,7 +103,7 @@ pub struct ExecutionSuccess {
   pub block_number: u64,
   pub log_index: usize,
   pub tx_hash: Vec<u8>,
-  pub amount: U256,
+  pub payment: U256,
   pub inner_tx_hash: Vec<u8>, // the tx_hash of the executed SAFE tx (of the router withdrawal for example)
 }
 
@@ -51,11 +115,52 @@ pub struct ExecResult {
   pub block_number: u64,
 }
 
+impl From<TransactionResult> for ExecResult {
+  fn from(tx_result: TransactionResult) -> Self {
+    match tx_result {
+      TransactionResult::Hash(h) => ExecResult {
+        txid: h.0.to_vec(),
+        gas_used: 0,
+        gas_price: 0,
+        block_number: 0,
+      }, // should not ever happen
+      TransactionResult::Receipt(r) => {
+        let gas = if let Some(g) = r.gas_used {
+          g.as_u128()
+        } else {
+          0
+        };
+        let gas_price = if let Some(gp) = r.effective_gas_price {
+          gp.as_u128()
+        } else {
+          0
+        };
+        let block_number = if let Some(bn) = r.block_number {
+          bn.as_u64()
+        } else {
+          0
+        };
+        ExecResult {
+          txid: r.transaction_hash.0.to_vec(),
+          gas_used: gas,
+          gas_price,
+          block_number,
+        }
+      }
+    }
+  }
+}
+
 impl SafeClient {
   pub fn new(web3: &Web3<DynTransport>, address: Option<&str>) -> Result<Self> {
     Ok(Self {
       address: utils::address_or_default(address)?,
       web3: web3.clone(),
+      chain_id: None,
+      safe_version: None,
+      detected_version: OnceLock::new(),
+      simulate_accessor: None,
+      preflight: false,
     })
   }
 
@@ -65,6 +170,39 @@ impl SafeClient {
 
   pub fn set_address(&mut self, address: H160) {
     self.address = address;
+    // a different proxy may run a different singleton
+    self.detected_version = OnceLock::new();
+  }
+
+  /// Pin the chain id used for EIP-712 hashing, so encoding never needs an RPC call.
+  /// The hash is the same for every supported Safe version.
+  pub fn set_chain_id(&mut self, chain_id: u64) {
+    self.chain_id = Some(chain_id);
+  }
+
+  /// Pin the Safe version instead of detecting it
+  pub fn set_safe_version(&mut self, version: SafeVersion) {
+    self.safe_version = Some(version);
+  }
+
+  /// The deployed `SimulateTxAccessor` that `simulate` runs transactions through,
+  /// such as the one `2_factory` deploys
+  pub fn set_simulate_accessor(&mut self, accessor: Address) {
+    self.simulate_accessor = Some(accessor);
+  }
+
+  /// Verify signatures locally against the Safe's owners before every exec,
+  /// instead of paying for a reverted transaction
+  pub fn set_preflight(&mut self, enabled: bool) {
+    self.preflight = enabled;
+  }
+
+  pub async fn chain_id(&self) -> Result<u64> {
+    if let Some(c) = self.chain_id {
+      return Ok(c);
+    }
+    let c = self.web3.eth().chain_id().await?;
+    Ok(c.as_u64())
   }
 
   pub async fn deploy(&mut self, account: Account<DynTransport>) -> Result<String> {
@@ -81,10 +219,12 @@ impl SafeClient {
       .from(account)
       .deploy()
       .await?;
-    self.address = proxy.address();
+    self.set_address(proxy.address());
     Ok(format!("{:?}", proxy.address()))
   }
 
+  /// Deploy a GnosisSafe singleton for proxies to delegate to. Despite the name no
+  /// proxy factory is deployed, see `deploy_with_proxy_factory` for that.
   pub async fn deploy_factory(&mut self, account: Account<DynTransport>) -> Result<String> {
     let factory = GnosisSafe::builder(&self.web3)
       // .gas(6_000_000u64.into())
@@ -94,6 +234,7 @@ impl SafeClient {
     Ok(format!("{:?}", factory.address()))
   }
 
+  /// Deploy a bare proxy to the singleton at `factory_address`
   pub async fn deploy_with_factory(
     &mut self,
     account: Account<DynTransport>,
@@ -106,7 +247,7 @@ impl SafeClient {
       .from(account)
       .deploy()
       .await?;
-    self.address = proxy.address();
+    self.set_address(proxy.address());
     Ok(format!("{:?}", proxy.address()))
   }
 
@@ -122,6 +263,12 @@ impl SafeClient {
     Ok(os)
   }
 
+  pub async fn get_threshold(&self) -> Result<u64> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let t = safe.get_threshold().call().await?;
+    Ok(t.as_u64())
+  }
+
   pub async fn setup(
     &mut self,
     from_account: Account<DynTransport>,
@@ -155,38 +302,206 @@ impl SafeClient {
     Ok(true)
   }
 
+  pub async fn domain_separator(&self) -> Result<[u8; 32]> {
+    let chain_id = self.chain_id().await?;
+    Ok(eip712::domain_separator(chain_id, self.address))
+  }
+
+  /// A new transaction to `to` at the Safe's current nonce
+  pub async fn new_transaction(&self, to: Address) -> Result<SafeTransaction> {
+    let nonce = self.nonce().await?;
+    Ok(SafeTransaction::new(to, nonce))
+  }
+
+  /// Encode the transaction the same way `GnosisSafe.encodeTransactionData` does.
+  /// Owners sign the keccak256 of the returned bytes.
+  pub async fn encode_transaction(&self, tx: &SafeTransaction) -> Result<Vec<u8>> {
+    let chain_id = self.chain_id().await?;
+    Ok(tx.encode(chain_id, self.address))
+  }
+
+  /// The SafeTx hash, as returned by `GnosisSafe.getTransactionHash`
+  pub async fn safe_transaction_hash(&self, tx: &SafeTransaction) -> Result<[u8; 32]> {
+    let chain_id = self.chain_id().await?;
+    Ok(tx.hash(chain_id, self.address))
+  }
+
+  /// An empty signature set for `tx`, checked against the Safe's current owners and threshold
+  pub async fn signatures(&self, tx: &SafeTransaction) -> Result<SafeSignatures> {
+    let hash = self.safe_transaction_hash(tx).await?;
+    let owners = self.get_owners().await?;
+    let threshold = self.get_threshold().await?;
+    Ok(SafeSignatures::new(hash, owners, threshold))
+  }
+
+  /// Run the Safe's `checkNSignatures` logic locally against the current owners and threshold
+  /// `executor` is the account that will send `execTransaction`, whose `v = 1`
+  /// entry is accepted without an on-chain approval.
+  pub async fn verify_signatures(
+    &self,
+    tx: &SafeTransaction,
+    signatures: &[u8],
+    executor: Option<Address>,
+  ) -> Result<SignatureReport> {
+    let hash = self.safe_transaction_hash(tx).await?;
+    let owners = self.get_owners().await?;
+    let threshold = self.get_threshold().await?;
+    let entries = unpack_signatures(signatures, threshold);
+    let mut approved: Vec<Address> = executor.into_iter().collect();
+    let mut contract_valid = vec![];
+    for entry in &entries {
+      let owner = entry.r_owner();
+      match (entry.v(), &entry.contract_data) {
+        (0, Some(Ok(data))) => {
+          if self
+            .is_valid_contract_signature(tx, owner, data.clone())
+            .await?
+          {
+            contract_valid.push(owner);
+          }
+        }
+        (1, _) => {
+          if self.is_hash_approved(owner, hash).await? {
+            approved.push(owner);
+          }
+        }
+        _ => {}
+      }
+    }
+    Ok(check_unpacked_signatures(
+      hash,
+      &owners,
+      threshold,
+      &entries,
+      &approved,
+      &contract_valid,
+    ))
+  }
+
+  /// The hash the owners of this Safe sign so it can act as a contract owner of
+  /// another Safe, validating `message` through `isValidSignature`
+  pub async fn safe_message_hash(&self, message: &[u8]) -> Result<[u8; 32]> {
+    let chain_id = self.chain_id().await?;
+    Ok(eip712::safe_message_hash(chain_id, self.address, message))
+  }
+
+  /// Ask a contract owner whether `signature` is valid for `tx` on this Safe,
+  /// with the same `isValidSignature(txHashData, signature)` call `checkNSignatures` makes
+  pub async fn is_valid_contract_signature(
+    &self,
+    tx: &SafeTransaction,
+    owner: Address,
+    signature: Vec<u8>,
+  ) -> Result<bool> {
+    let tx_data = self.encode_transaction(tx).await?;
+    let data = utils::encode_call(
+      "isValidSignature",
+      &[ParamType::Bytes, ParamType::Bytes],
+      &[Token::Bytes(tx_data), Token::Bytes(signature)],
+    );
+    let call = CallRequest {
+      from: Some(self.address),
+      to: Some(owner),
+      data: Some(Web3Bytes(data)),
+      ..Default::default()
+    };
+    match self.web3.eth().call(call, None).await {
+      Ok(result) => Ok(result.0.len() >= 4 && result.0[0..4] == EIP1271_MAGIC_VALUE),
+      // the validator reverted
+      Err(ethcontract::web3::Error::Rpc(e)) => {
+        log::debug!("isValidSignature reverted: {:?}", e);
+        Ok(false)
+      }
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  /// Validate a contract owner's signature on chain and add it to the set
+  pub async fn add_contract_signature(
+    &self,
+    tx: &SafeTransaction,
+    signatures: &mut SafeSignatures,
+    owner: Address,
+    signature: Vec<u8>,
+  ) -> Result<()> {
+    if !self
+      .is_valid_contract_signature(tx, owner, signature.clone())
+      .await?
+    {
+      return Err(Error::InvalidSignature(format!(
+        "{:?} rejected the contract signature",
+        owner
+      )));
+    }
+    signatures.add_contract_signature(owner, signature)
+  }
+
+  /// Approve a SafeTx hash on chain, as an alternative to signing it.
+  /// `from_account` must be an owner of the Safe.
+  pub async fn approve_hash(
+    &self,
+    from_account: Account<DynTransport>,
+    hash: [u8; 32],
+  ) -> Result<ExecResult> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let nonce = self
+      .web3
+      .eth()
+      .transaction_count(from_account.address(), None)
+      .await?;
+    let tx_result = safe
+      .approve_hash(Bytes(hash))
+      .from(from_account)
+      .nonce(nonce)
+      .send()
+      .await?;
+    Ok(tx_result.into())
+  }
+
+  pub async fn is_hash_approved(&self, owner: Address, hash: [u8; 32]) -> Result<bool> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let approved = safe.approved_hashes(owner, Bytes(hash)).call().await?;
+    Ok(!approved.is_zero())
+  }
+
+  /// Add a `v = 1` entry for every owner who has approved the set's hash on chain,
+  /// returning the owners that were added
+  pub async fn add_approvals(&self, signatures: &mut SafeSignatures) -> Result<Vec<Address>> {
+    let mut added = vec![];
+    let signed = signatures.signers();
+    for owner in self.get_owners().await? {
+      if !signed.contains(&owner) && self.is_hash_approved(owner, signatures.hash()).await? {
+        signatures.add_approved_hash(owner)?;
+        added.push(owner);
+      }
+    }
+    Ok(added)
+  }
+
   pub async fn encode_data(
     &self,
     address: Address,
     amount: u128,
     data: Vec<u8>,
     nonce: u64,
-    gas: Option<u128>,
   ) -> Result<Bytes<Vec<u8>>> {
-    let safe = GnosisSafe::at(&self.web3, self.address);
-    let address_0: Address = utils::zero_address();
-    // Get the hash of the transaction according to how Gnosis will handle it
-    let tx_data = safe.encode_transaction_data(
-      // TX
-      address,
-      amount.into(),
-      Bytes(data),
-      0,
-      // Refund data
-      0_u64.into(),
-      0_u64.into(),
-      0_u64.into(),
-      address_0,
-      address_0,
-      // Nonce
-      nonce.into(),
-    );
-    let res = if let Some(g) = gas {
-      tx_data.gas(g.into()).call().await?
-    } else {
-      tx_data.call().await?
-    };
-    Ok(res)
+    let tx = SafeTransaction::new(address, nonce)
+      .value(amount.into())
+      .data(data);
+    Ok(Bytes(self.encode_transaction(&tx).await?))
+  }
+
+  pub async fn transaction_hash(
+    &self,
+    address: Address,
+    amount: u128,
+    data: Vec<u8>,
+    nonce: u64,
+  ) -> Result<[u8; 32]> {
+    let tx = SafeTransaction::new(address, nonce)
+      .value(amount.into())
+      .data(data);
+    self.safe_transaction_hash(&tx).await
   }
 
   fn _make_erc20_data(&self, to: H160, amount: u128) -> Result<Vec<u8>> {
@@ -229,20 +544,13 @@ impl SafeClient {
     to: H160,
     amount: u128,
     nonce: u64,
-    gas: Option<u128>,
   ) -> Result<Bytes<Vec<u8>>> {
     let data = self._make_erc20_data(to, amount)?;
-    Ok(self.encode_data(erc20_address, 0, data, nonce, gas).await?)
+    Ok(self.encode_data(erc20_address, 0, data, nonce).await?)
   }
 
-  pub async fn encode_eth_tx(
-    &self,
-    to: H160,
-    amount: u128,
-    nonce: u64,
-    gas: Option<u128>,
-  ) -> Result<Bytes<Vec<u8>>> {
-    Ok(self.encode_data(to, amount, Vec::new(), nonce, gas).await?)
+  pub async fn encode_eth_tx(&self, to: H160, amount: u128, nonce: u64) -> Result<Bytes<Vec<u8>>> {
+    Ok(self.encode_data(to, amount, Vec::new(), nonce).await?)
   }
 
   pub async fn exec_erc20_tx(
@@ -255,10 +563,11 @@ impl SafeClient {
     gas: Option<u128>,
   ) -> Result<ExecResult> {
     let data = self._make_erc20_data(to, amount)?;
+    let tx = self.new_transaction(erc20_address).await?.data(data);
     let safe = GnosisSafe::at(&self.web3, self.address);
     Ok(
       self
-        ._exec(&safe, from_account, erc20_address, 0, data, signatures, gas)
+        ._exec(&safe, from_account, &tx, signatures, gas)
         .await?,
     )
   }
@@ -271,10 +580,11 @@ impl SafeClient {
     signatures: Vec<u8>,
     gas: Option<u128>,
   ) -> Result<ExecResult> {
+    let tx = self.new_transaction(to).await?.value(amount.into());
     let safe = GnosisSafe::at(&self.web3, self.address);
     Ok(
       self
-        ._exec(&safe, from_account, to, amount, Vec::new(), signatures, gas)
+        ._exec(&safe, from_account, &tx, signatures, gas)
         .await?,
     )
   }
@@ -288,45 +598,98 @@ impl SafeClient {
     value: u128,
     gas: Option<u128>,
   ) -> Result<ExecResult> {
+    let tx = self
+      .new_transaction(address)
+      .await?
+      .value(value.into())
+      .data(data);
     let safe = GnosisSafe::at(&self.web3, self.address);
     Ok(
       self
-        ._exec(&safe, from_account, address, value, data, signatures, gas)
+        ._exec(&safe, from_account, &tx, signatures, gas)
         .await?,
     )
   }
 
+  /// Execute a signed SafeTransaction. Fails before broadcasting if the Safe has
+  /// already moved past (or not yet reached) the nonce the transaction was signed with.
+  pub async fn exec_transaction(
+    &self,
+    from_account: Account<DynTransport>,
+    tx: &SafeTransaction,
+    signatures: Vec<u8>,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let nonce = safe.nonce().call().await?;
+    if nonce != tx.nonce {
+      return Err(Error::Other(format!(
+        "safe nonce is {} but transaction was signed for nonce {}",
+        nonce, tx.nonce
+      )));
+    }
+    Ok(self._exec(&safe, from_account, tx, signatures, gas).await?)
+  }
+
+  /// Execute `tx` with a signature set, checking the set was collected for `tx`
+  pub async fn exec_signed(
+    &self,
+    from_account: Account<DynTransport>,
+    tx: &SafeTransaction,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    if self.safe_transaction_hash(tx).await? != signatures.hash() {
+      return Err(Error::InvalidSignature(
+        "signatures were collected for a different transaction".to_string(),
+      ));
+    }
+    self
+      .exec_transaction(from_account, tx, signatures.encode()?, gas)
+      .await
+  }
+
   async fn _exec(
     &self,
     safe: &gnosis_safe::Contract,
     from_account: Account<DynTransport>,
-    to: Address,
-    amount: u128,
-    data: Vec<u8>,
+    safe_tx: &SafeTransaction,
     signatures: Vec<u8>,
     gas: Option<u128>,
   ) -> Result<ExecResult> {
+    if self.preflight {
+      let report = self
+        .verify_signatures(safe_tx, &signatures, Some(from_account.address()))
+        .await?;
+      if !report.is_valid() {
+        log::warn!("preflight signature check failed: {:?}", report.checks);
+        return Err(Error::InvalidSignature(format!(
+          "{} of {} required signatures are valid",
+          report.valid_count(),
+          report.threshold
+        )));
+      }
+    }
     let nonce = self
       .web3
       .eth()
       .transaction_count(from_account.address(), None)
       .await?;
     // let gas_price = self.web3.eth().gas_price().await?;
-    let address_0: Address = utils::zero_address();
     let tx = safe
       .exec_transaction(
-        to,
-        amount.into(),
-        Bytes(data.clone()),
-        0,
-        0_u64.into(),
-        0_u64.into(),
-        0_u64.into(),
-        address_0,
-        address_0,
+        safe_tx.to,
+        safe_tx.value,
+        Bytes(safe_tx.data.clone()),
+        safe_tx.operation as u8,
+        safe_tx.safe_tx_gas,
+        safe_tx.base_gas,
+        safe_tx.gas_price,
+        safe_tx.gas_token,
+        safe_tx.refund_receiver,
         // Nonce isn't included as it's a SC global
         // Signatures
-        Bytes(signatures.clone()),
+        Bytes(signatures),
       )
       .from(from_account.clone())
       .nonce(nonce);
@@ -355,38 +718,20 @@ impl SafeClient {
         }
       }
     };
-    log::info!("exec_transaction succeeded!");
-    Ok(match tx_result {
-      TransactionResult::Hash(h) => ExecResult {
-        txid: h.0.to_vec(),
-        gas_used: 0,
-        gas_price: 0,
-        block_number: 0,
-      }, // should not ever happen
-      TransactionResult::Receipt(r) => {
-        let gas = if let Some(g) = r.gas_used {
-          g.as_u128()
-        } else {
-          0
-        };
-        let gas_price = if let Some(gp) = r.effective_gas_price {
-          gp.as_u128()
-        } else {
-          0
-        };
-        let block_number = if let Some(bn) = r.block_number {
-          bn.as_u64()
-        } else {
-          0
-        };
-        ExecResult {
-          txid: r.transaction_hash.0.to_vec(),
-          gas_used: gas,
-          gas_price,
-          block_number,
-        }
+    // the inner call reverting still mines execTransaction and consumes the nonce
+    if let TransactionResult::Receipt(r) = &tx_result {
+      if let Some(SafeEvent::ExecutionFailure { tx_hash, .. }) =
+        execution_result(self.address, &r.logs)
+      {
+        log::warn!("safe transaction 0x{} failed", hex::encode(tx_hash));
+        return Err(Error::SafeExecutionFailure(
+          H256(tx_hash),
+          r.transaction_hash,
+        ));
       }
-    })
+    }
+    log::info!("exec_transaction succeeded!");
+    Ok(tx_result.into())
   }
 
   pub async fn estimate_gas_with_data(
@@ -396,11 +741,27 @@ impl SafeClient {
     data: Vec<u8>,
     signatures: Vec<u8>,
     value: u128,
+  ) -> Result<u128> {
+    let tx = self
+      .new_transaction(address)
+      .await?
+      .value(value.into())
+      .data(data);
+    self
+      .estimate_gas_transaction(from_account, &tx, signatures)
+      .await
+  }
+
+  pub async fn estimate_gas_transaction(
+    &self,
+    from_account: Account<DynTransport>,
+    tx: &SafeTransaction,
+    signatures: Vec<u8>,
   ) -> Result<u128> {
     let safe = GnosisSafe::at(&self.web3, self.address);
     Ok(
       self
-        ._estimate_gas(&safe, from_account, address, value, data, signatures)
+        ._estimate_gas(&safe, from_account, tx, signatures)
         .await?,
     )
   }
@@ -409,9 +770,7 @@ impl SafeClient {
     &self,
     safe: &gnosis_safe::Contract,
     from_account: Account<DynTransport>,
-    to: Address,
-    amount: u128,
-    data: Vec<u8>,
+    safe_tx: &SafeTransaction,
     signatures: Vec<u8>,
   ) -> Result<u128> {
     let nonce = self
@@ -419,18 +778,17 @@ impl SafeClient {
       .eth()
       .transaction_count(from_account.address(), None)
       .await?;
-    let address_0: Address = utils::zero_address();
     let tx = safe
       .exec_transaction(
-        to,
-        amount.into(),
-        Bytes(data),
-        0,
-        0_u64.into(),
-        0_u64.into(),
-        0_u64.into(),
-        address_0,
-        address_0,
+        safe_tx.to,
+        safe_tx.value,
+        Bytes(safe_tx.data.clone()),
+        safe_tx.operation as u8,
+        safe_tx.safe_tx_gas,
+        safe_tx.base_gas,
+        safe_tx.gas_price,
+        safe_tx.gas_token,
+        safe_tx.refund_receiver,
         Bytes(signatures),
       )
       .from(from_account)
@@ -441,33 +799,21 @@ impl SafeClient {
   }
 
   pub async fn get_execution_logs(&self, since: Option<u64>) -> Result<Vec<ExecutionSuccess>> {
-    let safe = GnosisSafe::at(&self.web3, self.address);
-    let bn: BlockNumber = match since {
-      Some(s) => s.into(),
-      None => BlockNumber::Earliest,
-    };
-    let events = safe.all_events().from_block(bn).query().await?;
-    Ok(self.events_to_execution_successes(events).await)
-  }
-
-  async fn events_to_execution_successes(
-    &self,
-    events: Vec<Event<gnosis_safe::Event>>,
-  ) -> Vec<ExecutionSuccess> {
-    let mut ret: Vec<ExecutionSuccess> = vec![];
-    events.iter().for_each(|e| {
-      if let gnosis_safe::Event::ExecutionSuccess(exe) = &e.data {
-        if let Some(meta) = &e.meta {
-          ret.push(ExecutionSuccess {
-            tx_hash: meta.transaction_hash.0.to_vec(),
-            block_number: meta.block_number,
-            log_index: meta.log_index,
-            amount: exe.payment,
-            inner_tx_hash: exe.tx_hash.0.to_vec(),
-          });
-        }
-      }
-    });
-    ret
+    let events = self.get_events(since).await?;
+    Ok(
+      events
+        .into_iter()
+        .filter_map(|e| match e.event {
+          SafeEvent::ExecutionSuccess { tx_hash, payment } => Some(ExecutionSuccess {
+            tx_hash: e.tx_hash,
+            block_number: e.block_number,
+            log_index: e.log_index,
+            payment,
+            inner_tx_hash: tx_hash.to_vec(),
+          }),
+          _ => None,
+        })
+        .collect(),
+    )
   }
 }
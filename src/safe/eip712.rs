@@ -0,0 +1,115 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! EIP-712 encoding of Gnosis Safe transactions, matching `GnosisSafe.encodeTransactionData`
+//! and `GnosisSafe.getTransactionHash` byte for byte.
+
+use ethcontract::{
+  prelude::*,
+  web3::ethabi::{encode, Token},
+};
+use tiny_keccak::{Hasher, Keccak};
+
+/// keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")
+pub const DOMAIN_SEPARATOR_TYPEHASH: [u8; 32] = [
+  0x47, 0xe7, 0x95, 0x34, 0xa2, 0x45, 0x95, 0x2e, 0x8b, 0x16, 0x89, 0x3a, 0x33, 0x6b, 0x85, 0xa3,
+  0xd9, 0xea, 0x9f, 0xa8, 0xc5, 0x73, 0xf3, 0xd8, 0x03, 0xaf, 0xb9, 0x2a, 0x79, 0x46, 0x92, 0x18,
+];
+
+/// keccak256("SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)")
+pub const SAFE_TX_TYPEHASH: [u8; 32] = [
+  0xbb, 0x83, 0x10, 0xd4, 0x86, 0x36, 0x8d, 0xb6, 0xbd, 0x6f, 0x84, 0x94, 0x02, 0xfd, 0xd7, 0x3a,
+  0xd5, 0x3d, 0x31, 0x6b, 0x5a, 0x4b, 0x26, 0x44, 0xad, 0x6e, 0xfe, 0x0f, 0x94, 0x12, 0x86, 0xd8,
+];
+
+/// keccak256("SafeMessage(bytes message)"), used by the fallback handler for EIP-1271
+pub const SAFE_MSG_TYPEHASH: [u8; 32] = [
+  0x60, 0xb3, 0xcb, 0xf8, 0xb4, 0xa2, 0x23, 0xd6, 0x8d, 0x64, 0x1b, 0x3b, 0x6d, 0xdf, 0x9a, 0x29,
+  0x8e, 0x7f, 0x33, 0x71, 0x0c, 0xf3, 0xd3, 0xa9, 0xd1, 0x14, 0x6b, 0x5a, 0x61, 0x50, 0xfb, 0xca,
+];
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+  let mut output = [0u8; 32];
+  let mut hasher = Keccak::v256();
+  hasher.update(bytes);
+  hasher.finalize(&mut output);
+  output
+}
+
+/// The Safe's EIP-712 domain separator, as returned by `GnosisSafe.domainSeparator`.
+pub fn domain_separator(chain_id: u64, safe: Address) -> [u8; 32] {
+  keccak256(&encode(&[
+    Token::FixedBytes(DOMAIN_SEPARATOR_TYPEHASH.to_vec()),
+    Token::Uint(chain_id.into()),
+    Token::Address(safe),
+  ]))
+}
+
+/// The hash of the `SafeTx` struct, before it is wrapped with the domain separator.
+#[allow(clippy::too_many_arguments)]
+pub fn safe_tx_struct_hash(
+  to: Address,
+  value: U256,
+  data: &[u8],
+  operation: u8,
+  safe_tx_gas: U256,
+  base_gas: U256,
+  gas_price: U256,
+  gas_token: Address,
+  refund_receiver: Address,
+  nonce: U256,
+) -> [u8; 32] {
+  keccak256(&encode(&[
+    Token::FixedBytes(SAFE_TX_TYPEHASH.to_vec()),
+    Token::Address(to),
+    Token::Uint(value),
+    Token::FixedBytes(keccak256(data).to_vec()),
+    Token::Uint(operation.into()),
+    Token::Uint(safe_tx_gas),
+    Token::Uint(base_gas),
+    Token::Uint(gas_price),
+    Token::Address(gas_token),
+    Token::Address(refund_receiver),
+    Token::Uint(nonce),
+  ]))
+}
+
+/// `0x19 || 0x01 || domainSeparator || safeTxHash`, the preimage the owners sign the hash of.
+pub fn encode_transaction_data(
+  domain_separator: [u8; 32],
+  safe_tx_struct_hash: [u8; 32],
+) -> Vec<u8> {
+  let mut encoded = Vec::with_capacity(66);
+  encoded.push(0x19);
+  encoded.push(0x01);
+  encoded.extend_from_slice(&domain_separator);
+  encoded.extend_from_slice(&safe_tx_struct_hash);
+  encoded
+}
+
+/// The hash the owners of `safe` sign so that `safe` can validate `message` through
+/// `isValidSignature`, as computed by the Safe's compatibility fallback handler.
+/// This is how a Safe that is itself an owner signs for a parent Safe.
+pub fn safe_message_hash(chain_id: u64, safe: Address, message: &[u8]) -> [u8; 32] {
+  let struct_hash = keccak256(&encode(&[
+    Token::FixedBytes(SAFE_MSG_TYPEHASH.to_vec()),
+    Token::FixedBytes(keccak256(message).to_vec()),
+  ]));
+  keccak256(&encode_transaction_data(
+    domain_separator(chain_id, safe),
+    struct_hash,
+  ))
+}
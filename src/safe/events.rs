@@ -0,0 +1,322 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Typed history of everything the Safe emits, for auditing the custody Safe.
+
+use super::SafeClient;
+use crate::Result;
+use ethcontract::{
+  prelude::*,
+  web3::{
+    ethabi::{decode, param_type::ParamType, Token},
+    types::{FilterBuilder, Log},
+  },
+};
+
+/// keccak256("ExecutionSuccess(bytes32,uint256)")
+pub const EXECUTION_SUCCESS_TOPIC: [u8; 32] = [
+  0x44, 0x2e, 0x71, 0x5f, 0x62, 0x63, 0x46, 0xe8, 0xc5, 0x43, 0x81, 0x00, 0x2d, 0xa6, 0x14, 0xf6,
+  0x2b, 0xee, 0x8d, 0x27, 0x38, 0x65, 0x35, 0xb2, 0x52, 0x1e, 0xc8, 0x54, 0x08, 0x98, 0x55, 0x6e,
+];
+
+/// keccak256("ExecutionFailure(bytes32,uint256)")
+pub const EXECUTION_FAILURE_TOPIC: [u8; 32] = [
+  0x23, 0x42, 0x8b, 0x18, 0xac, 0xfb, 0x3e, 0xa6, 0x4b, 0x08, 0xdc, 0x0c, 0x1d, 0x29, 0x6e, 0xa9,
+  0xc0, 0x97, 0x02, 0xc0, 0x90, 0x83, 0xca, 0x52, 0x72, 0xe6, 0x4d, 0x11, 0x5b, 0x68, 0x7d, 0x23,
+];
+
+/// keccak256("SafeSetup(address,address[],uint256,address,address)")
+const SAFE_SETUP_TOPIC: [u8; 32] = [
+  0x14, 0x1d, 0xf8, 0x68, 0xa6, 0x33, 0x1a, 0xf5, 0x28, 0xe3, 0x8c, 0x83, 0xb7, 0xaa, 0x03, 0xed,
+  0xc1, 0x9b, 0xe6, 0x6e, 0x37, 0xae, 0x67, 0xf9, 0x28, 0x5b, 0xf4, 0xf8, 0xe3, 0xc6, 0xa1, 0xa8,
+];
+
+/// keccak256("AddedOwner(address)")
+const ADDED_OWNER_TOPIC: [u8; 32] = [
+  0x94, 0x65, 0xfa, 0x0c, 0x96, 0x2c, 0xc7, 0x69, 0x58, 0xe6, 0x37, 0x3a, 0x99, 0x33, 0x26, 0x40,
+  0x0c, 0x1c, 0x94, 0xf8, 0xbe, 0x2f, 0xe3, 0xa9, 0x52, 0xad, 0xfa, 0x7f, 0x60, 0xb2, 0xea, 0x26,
+];
+
+/// keccak256("RemovedOwner(address)")
+const REMOVED_OWNER_TOPIC: [u8; 32] = [
+  0xf8, 0xd4, 0x9f, 0xc5, 0x29, 0x81, 0x2e, 0x9a, 0x7c, 0x5c, 0x50, 0xe6, 0x9c, 0x20, 0xf0, 0xdc,
+  0xcc, 0x0d, 0xb8, 0xfa, 0x95, 0xc9, 0x8b, 0xc5, 0x8c, 0xc9, 0xa4, 0xf1, 0xc1, 0x29, 0x9e, 0xaf,
+];
+
+/// keccak256("ChangedThreshold(uint256)")
+const CHANGED_THRESHOLD_TOPIC: [u8; 32] = [
+  0x61, 0x0f, 0x7f, 0xf2, 0xb3, 0x04, 0xae, 0x89, 0x03, 0xc3, 0xde, 0x74, 0xc6, 0x0c, 0x6a, 0xb1,
+  0xf7, 0xd6, 0x22, 0x6b, 0x3f, 0x52, 0xc5, 0x16, 0x19, 0x05, 0xbb, 0x5a, 0xd4, 0x03, 0x9c, 0x93,
+];
+
+/// keccak256("ApproveHash(bytes32,address)")
+const APPROVE_HASH_TOPIC: [u8; 32] = [
+  0xf2, 0xa0, 0xeb, 0x15, 0x64, 0x72, 0xd1, 0x44, 0x02, 0x55, 0xb0, 0xd7, 0xc1, 0xe1, 0x9c, 0xc0,
+  0x71, 0x15, 0xd1, 0x05, 0x1f, 0xe6, 0x05, 0xb0, 0xdc, 0xe6, 0x9a, 0xcf, 0xec, 0x88, 0x4d, 0x9c,
+];
+
+/// keccak256("SignMsg(bytes32)")
+const SIGN_MSG_TOPIC: [u8; 32] = [
+  0xe7, 0xf4, 0x67, 0x50, 0x38, 0xf4, 0xf6, 0x03, 0x4d, 0xfc, 0xbb, 0xb2, 0x4c, 0x4d, 0xc0, 0x8e,
+  0x4e, 0xbf, 0x10, 0xeb, 0x9d, 0x25, 0x7d, 0x3d, 0x02, 0xc0, 0xf3, 0x8d, 0x12, 0x2a, 0xc6, 0xe4,
+];
+
+/// keccak256("EnabledModule(address)")
+const ENABLED_MODULE_TOPIC: [u8; 32] = [
+  0xec, 0xdf, 0x3a, 0x3e, 0xff, 0xea, 0x57, 0x83, 0xa3, 0xc4, 0xc2, 0x14, 0x0e, 0x67, 0x75, 0x77,
+  0x66, 0x64, 0x28, 0xd4, 0x4e, 0xd9, 0xd4, 0x74, 0xa0, 0xb3, 0xa4, 0xc9, 0x94, 0x3f, 0x84, 0x40,
+];
+
+/// keccak256("DisabledModule(address)")
+const DISABLED_MODULE_TOPIC: [u8; 32] = [
+  0xaa, 0xb4, 0xfa, 0x2b, 0x46, 0x3f, 0x58, 0x1b, 0x2b, 0x32, 0xcb, 0x3b, 0x7e, 0x3b, 0x70, 0x4b,
+  0x9c, 0xe3, 0x7c, 0xc2, 0x09, 0xb5, 0xfb, 0x4d, 0x77, 0xe5, 0x93, 0xac, 0xe4, 0x05, 0x42, 0x76,
+];
+
+/// keccak256("ExecutionFromModuleSuccess(address)")
+const EXECUTION_FROM_MODULE_SUCCESS_TOPIC: [u8; 32] = [
+  0x68, 0x95, 0xc1, 0x36, 0x64, 0xaa, 0x4f, 0x67, 0x28, 0x8b, 0x25, 0xd7, 0xa2, 0x1d, 0x7a, 0xaa,
+  0x34, 0x91, 0x6e, 0x35, 0x5f, 0xb9, 0xb6, 0xfa, 0xe0, 0xa1, 0x39, 0xa9, 0x08, 0x5b, 0xec, 0xb8,
+];
+
+/// keccak256("ExecutionFromModuleFailure(address)")
+const EXECUTION_FROM_MODULE_FAILURE_TOPIC: [u8; 32] = [
+  0xac, 0xd2, 0xc8, 0x70, 0x28, 0x04, 0x12, 0x8f, 0xdb, 0x0d, 0xb2, 0xbb, 0x49, 0xf6, 0xd1, 0x27,
+  0xdd, 0x01, 0x81, 0xc1, 0x3f, 0xd4, 0x5d, 0xbf, 0xe1, 0x6d, 0xe0, 0x93, 0x0e, 0x2b, 0xd3, 0x75,
+];
+
+/// keccak256("ChangedGuard(address)")
+const CHANGED_GUARD_TOPIC: [u8; 32] = [
+  0x11, 0x51, 0x11, 0x69, 0x14, 0x51, 0x5b, 0xc0, 0x89, 0x1f, 0xf9, 0x04, 0x7a, 0x6c, 0xb3, 0x2c,
+  0xf9, 0x02, 0x54, 0x6f, 0x83, 0x06, 0x64, 0x99, 0xbc, 0xf8, 0xba, 0x33, 0xd2, 0x35, 0x3f, 0xa2,
+];
+
+/// keccak256("ChangedFallbackHandler(address)")
+const CHANGED_FALLBACK_HANDLER_TOPIC: [u8; 32] = [
+  0x5a, 0xc6, 0xc4, 0x6c, 0x93, 0xc8, 0xd0, 0xe5, 0x37, 0x14, 0xba, 0x3b, 0x53, 0xdb, 0x3e, 0x7c,
+  0x04, 0x6d, 0xa9, 0x94, 0x31, 0x3d, 0x7e, 0xd0, 0xd1, 0x92, 0x02, 0x8b, 0xc7, 0xc2, 0x28, 0xb0,
+];
+
+/// keccak256("SafeReceived(address,uint256)")
+const SAFE_RECEIVED_TOPIC: [u8; 32] = [
+  0x3d, 0x0c, 0xe9, 0xbf, 0xc3, 0xed, 0x7d, 0x68, 0x62, 0xdb, 0xb2, 0x8b, 0x2d, 0xea, 0x94, 0x56,
+  0x1f, 0xe7, 0x14, 0xa1, 0xb4, 0xd0, 0x19, 0xaa, 0x8a, 0xf3, 0x97, 0x30, 0xd1, 0xad, 0x7c, 0x3d,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SafeEvent {
+  SafeSetup {
+    initiator: Address,
+    owners: Vec<Address>,
+    threshold: U256,
+    initializer: Address,
+    fallback_handler: Address,
+  },
+  AddedOwner {
+    owner: Address,
+  },
+  RemovedOwner {
+    owner: Address,
+  },
+  ChangedThreshold {
+    threshold: U256,
+  },
+  ApproveHash {
+    approved_hash: [u8; 32],
+    owner: Address,
+  },
+  SignMsg {
+    msg_hash: [u8; 32],
+  },
+  ExecutionSuccess {
+    tx_hash: [u8; 32],
+    payment: U256,
+  },
+  ExecutionFailure {
+    tx_hash: [u8; 32],
+    payment: U256,
+  },
+  EnabledModule {
+    module: Address,
+  },
+  DisabledModule {
+    module: Address,
+  },
+  ExecutionFromModuleSuccess {
+    module: Address,
+  },
+  ExecutionFromModuleFailure {
+    module: Address,
+  },
+  ChangedGuard {
+    guard: Address,
+  },
+  ChangedFallbackHandler {
+    handler: Address,
+  },
+  SafeReceived {
+    sender: Address,
+    value: U256,
+  },
+}
+
+/// A Safe event and where it was emitted
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeEventLog {
+  pub block_number: u64,
+  pub tx_hash: Vec<u8>,
+  pub log_index: usize,
+  pub event: SafeEvent,
+}
+
+/// Decode a Safe log of any supported version. 1.4.0 indexes the owner, module, guard,
+/// handler and SafeTx hash params that 1.3.0 keeps in the data, but indexed params always
+/// come first, so the topics followed by the data words are the params in order either way.
+pub fn decode_log(log: &Log) -> Option<SafeEvent> {
+  let topic = log.topics.first()?.0;
+  if topic == SAFE_SETUP_TOPIC {
+    let initiator = H160::from_slice(&log.topics.get(1)?.0[12..]);
+    let params = [
+      ParamType::Array(Box::new(ParamType::Address)),
+      ParamType::Uint(256),
+      ParamType::Address,
+      ParamType::Address,
+    ];
+    let mut tokens = decode(&params, &log.data.0).ok()?.into_iter();
+    let owners = tokens.next()?.into_array()?;
+    return Some(SafeEvent::SafeSetup {
+      initiator,
+      owners: owners.into_iter().filter_map(Token::into_address).collect(),
+      threshold: tokens.next()?.into_uint()?,
+      initializer: tokens.next()?.into_address()?,
+      fallback_handler: tokens.next()?.into_address()?,
+    });
+  }
+
+  let mut words: Vec<&[u8]> = log.topics[1..].iter().map(|t| t.as_bytes()).collect();
+  words.extend(log.data.0.chunks(32));
+  let word = |i: usize| words.get(i).copied().filter(|w| w.len() == 32);
+  let address = |i: usize| word(i).map(|w| H160::from_slice(&w[12..]));
+  let uint = |i: usize| word(i).map(U256::from_big_endian);
+  let bytes32 = |i: usize| {
+    word(i).map(|w| {
+      let mut b = [0u8; 32];
+      b.copy_from_slice(w);
+      b
+    })
+  };
+
+  if topic == ADDED_OWNER_TOPIC {
+    Some(SafeEvent::AddedOwner { owner: address(0)? })
+  } else if topic == REMOVED_OWNER_TOPIC {
+    Some(SafeEvent::RemovedOwner { owner: address(0)? })
+  } else if topic == CHANGED_THRESHOLD_TOPIC {
+    Some(SafeEvent::ChangedThreshold {
+      threshold: uint(0)?,
+    })
+  } else if topic == APPROVE_HASH_TOPIC {
+    Some(SafeEvent::ApproveHash {
+      approved_hash: bytes32(0)?,
+      owner: address(1)?,
+    })
+  } else if topic == SIGN_MSG_TOPIC {
+    Some(SafeEvent::SignMsg {
+      msg_hash: bytes32(0)?,
+    })
+  } else if topic == EXECUTION_SUCCESS_TOPIC {
+    Some(SafeEvent::ExecutionSuccess {
+      tx_hash: bytes32(0)?,
+      payment: uint(1)?,
+    })
+  } else if topic == EXECUTION_FAILURE_TOPIC {
+    Some(SafeEvent::ExecutionFailure {
+      tx_hash: bytes32(0)?,
+      payment: uint(1)?,
+    })
+  } else if topic == ENABLED_MODULE_TOPIC {
+    Some(SafeEvent::EnabledModule {
+      module: address(0)?,
+    })
+  } else if topic == DISABLED_MODULE_TOPIC {
+    Some(SafeEvent::DisabledModule {
+      module: address(0)?,
+    })
+  } else if topic == EXECUTION_FROM_MODULE_SUCCESS_TOPIC {
+    Some(SafeEvent::ExecutionFromModuleSuccess {
+      module: address(0)?,
+    })
+  } else if topic == EXECUTION_FROM_MODULE_FAILURE_TOPIC {
+    Some(SafeEvent::ExecutionFromModuleFailure {
+      module: address(0)?,
+    })
+  } else if topic == CHANGED_GUARD_TOPIC {
+    Some(SafeEvent::ChangedGuard { guard: address(0)? })
+  } else if topic == CHANGED_FALLBACK_HANDLER_TOPIC {
+    Some(SafeEvent::ChangedFallbackHandler {
+      handler: address(0)?,
+    })
+  } else if topic == SAFE_RECEIVED_TOPIC {
+    Some(SafeEvent::SafeReceived {
+      sender: address(0)?,
+      value: uint(1)?,
+    })
+  } else {
+    None
+  }
+}
+
+/// The `ExecutionSuccess` or `ExecutionFailure` that `safe` emitted among a receipt's logs
+pub fn execution_result(safe: Address, logs: &[Log]) -> Option<SafeEvent> {
+  logs
+    .iter()
+    .filter(|l| l.address == safe)
+    .filter_map(decode_log)
+    .find(|e| {
+      matches!(
+        e,
+        SafeEvent::ExecutionSuccess { .. } | SafeEvent::ExecutionFailure { .. }
+      )
+    })
+}
+
+impl SafeClient {
+  /// Every event the Safe emitted since `since` (or ever), oldest first.
+  /// Logs are decoded with `decode_log`, so 1.4.x Safes are read as well as 1.3.0.
+  pub async fn get_events(&self, since: Option<u64>) -> Result<Vec<SafeEventLog>> {
+    let bn: BlockNumber = match since {
+      Some(s) => s.into(),
+      None => BlockNumber::Earliest,
+    };
+    let filter = FilterBuilder::default()
+      .address(vec![self.address])
+      .from_block(bn)
+      .build();
+    let logs = self.web3.eth().logs(filter).await?;
+    Ok(
+      logs
+        .into_iter()
+        .filter_map(|l| {
+          Some(SafeEventLog {
+            block_number: l.block_number?.as_u64(),
+            tx_hash: l.transaction_hash?.0.to_vec(),
+            log_index: l.log_index?.as_usize(),
+            event: decode_log(&l)?,
+          })
+        })
+        .collect(),
+    )
+  }
+}
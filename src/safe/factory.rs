@@ -0,0 +1,213 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `GnosisSafeProxyFactory.createProxyWithNonce` support, and the CREATE2 address the
+//! factory will deploy a proxy to, computed offline so a Safe can be published and funded
+//! before it exists.
+
+use super::{eip712::keccak256, GnosisSafeProxy, SafeClient};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  transaction::{TransactionBuilder, TransactionResult},
+  transport::DynTransport,
+  web3::{
+    ethabi::{decode, param_type::ParamType, Token},
+    types::{Bytes as Web3Bytes, CallRequest, Log},
+  },
+};
+
+/// keccak256("ProxyCreation(address,address)")
+pub const PROXY_CREATION_TOPIC: [u8; 32] = [
+  0x4f, 0x51, 0xfa, 0xf6, 0xc4, 0x56, 0x1f, 0xf9, 0x5f, 0x06, 0x76, 0x57, 0xe4, 0x34, 0x39, 0xf0,
+  0xf8, 0x56, 0xd9, 0x7c, 0x04, 0xd9, 0xec, 0x90, 0x70, 0xa6, 0x19, 0x9a, 0xd4, 0x18, 0xe2, 0x35,
+];
+
+/// The CREATE2 salt `keccak256(keccak256(initializer) || saltNonce)`
+pub fn proxy_salt(initializer: &[u8], salt_nonce: U256) -> [u8; 32] {
+  let mut preimage = keccak256(initializer).to_vec();
+  let mut nonce = [0u8; 32];
+  salt_nonce.to_big_endian(&mut nonce);
+  preimage.extend_from_slice(&nonce);
+  keccak256(&preimage)
+}
+
+/// The creation code of the bundled `GnosisSafeProxy`, which is what a factory built
+/// from the same sources returns from `proxyCreationCode()`
+pub fn bundled_proxy_creation_code() -> Result<Vec<u8>> {
+  GnosisSafeProxy::raw_contract()
+    .bytecode
+    .to_bytes()
+    .map(|code| code.0)
+    .map_err(|e| Error::Other(format!("couldnt link GnosisSafeProxy bytecode: {}", e)))
+}
+
+/// The address `createProxyWithNonce(singleton, initializer, salt_nonce)` deploys to,
+/// computed offline from the bundled proxy creation code
+pub fn predict_proxy_address(
+  factory: Address,
+  singleton: Address,
+  initializer: &[u8],
+  salt_nonce: U256,
+) -> Result<Address> {
+  let code = bundled_proxy_creation_code()?;
+  Ok(predict_proxy_address_with_code(
+    factory,
+    &code,
+    singleton,
+    initializer,
+    salt_nonce,
+  ))
+}
+
+/// Like `predict_proxy_address`, for a factory whose `proxyCreationCode()` is
+/// `proxy_creation_code`
+pub fn predict_proxy_address_with_code(
+  factory: Address,
+  proxy_creation_code: &[u8],
+  singleton: Address,
+  initializer: &[u8],
+  salt_nonce: U256,
+) -> Address {
+  let mut init_code = proxy_creation_code.to_vec();
+  init_code.extend_from_slice(&[0u8; 12]);
+  init_code.extend_from_slice(singleton.as_bytes());
+
+  let mut preimage = vec![0xff];
+  preimage.extend_from_slice(factory.as_bytes());
+  preimage.extend_from_slice(&proxy_salt(initializer, salt_nonce));
+  preimage.extend_from_slice(&keccak256(&init_code));
+  H160::from_slice(&keccak256(&preimage)[12..])
+}
+
+pub fn create_proxy_with_nonce_data(
+  singleton: Address,
+  initializer: Vec<u8>,
+  salt_nonce: U256,
+) -> Vec<u8> {
+  utils::encode_call(
+    "createProxyWithNonce",
+    &[ParamType::Address, ParamType::Bytes, ParamType::Uint(256)],
+    &[
+      Token::Address(singleton),
+      Token::Bytes(initializer),
+      Token::Uint(salt_nonce),
+    ],
+  )
+}
+
+/// The proxy `factory` reports in the first `ProxyCreation` among a receipt's logs
+pub fn proxy_created(factory: Address, logs: &[Log]) -> Option<Address> {
+  let topic = H256(PROXY_CREATION_TOPIC);
+  logs
+    .iter()
+    .filter(|l| l.address == factory && l.topics.first() == Some(&topic))
+    // the proxy is indexed from 1.4.0
+    .find_map(|l| match l.topics.get(1) {
+      Some(proxy) => Some(H160::from_slice(&proxy.0[12..])),
+      None => l.data.0.get(12..32).map(H160::from_slice),
+    })
+}
+
+impl SafeClient {
+  /// The factory's `proxyCreationCode()`
+  pub async fn proxy_creation_code(&self, factory: Address) -> Result<Vec<u8>> {
+    let call = CallRequest {
+      to: Some(factory),
+      data: Some(Web3Bytes(utils::encode_call("proxyCreationCode", &[], &[]))),
+      ..Default::default()
+    };
+    let result = self.web3.eth().call(call, None).await?;
+    let tokens = decode(&[ParamType::Bytes], &result.0)
+      .map_err(|e| Error::Other(format!("couldnt decode proxyCreationCode: {}", e)))?;
+    tokens
+      .into_iter()
+      .next()
+      .and_then(|t| t.into_bytes())
+      .ok_or_else(|| Error::Other("couldnt decode proxyCreationCode".to_string()))
+  }
+
+  /// Check that `factory` creates the bundled proxy, so offline predictions hold for it
+  pub async fn check_proxy_creation_code(&self, factory: Address) -> Result<()> {
+    if self.proxy_creation_code(factory).await? != bundled_proxy_creation_code()? {
+      return Err(Error::Other(format!(
+        "{:?} creates a different proxy than the bundled GnosisSafeProxy",
+        factory
+      )));
+    }
+    Ok(())
+  }
+
+  /// The address a Safe created through `factory` with these arguments will have,
+  /// computed offline
+  pub fn predict_address(
+    &self,
+    factory: Address,
+    singleton: Address,
+    initializer: &[u8],
+    salt_nonce: U256,
+  ) -> Result<Address> {
+    predict_proxy_address(factory, singleton, initializer, salt_nonce)
+  }
+
+  /// Create a proxy through `factory.createProxyWithNonce` and point the client at it.
+  /// The `initializer` is called on the new proxy in the same transaction.
+  pub async fn deploy_with_proxy_factory(
+    &mut self,
+    account: Account<DynTransport>,
+    factory: Address,
+    singleton: Address,
+    initializer: Vec<u8>,
+    salt_nonce: U256,
+  ) -> Result<String> {
+    // what this factory will actually create, whatever proxy it was built with
+    let code = self.proxy_creation_code(factory).await?;
+    let predicted =
+      predict_proxy_address_with_code(factory, &code, singleton, &initializer, salt_nonce);
+    let data = create_proxy_with_nonce_data(singleton, initializer, salt_nonce);
+    let nonce = self
+      .web3
+      .eth()
+      .transaction_count(account.address(), None)
+      .await?;
+    let result = TransactionBuilder::new(self.web3.clone())
+      .from(account)
+      .nonce(nonce)
+      .to(factory)
+      .data(Web3Bytes(data))
+      .send()
+      .await?;
+    let receipt = match result {
+      TransactionResult::Receipt(r) => r,
+      TransactionResult::Hash(h) => {
+        return Err(Error::Other(format!(
+          "no receipt for proxy creation {:?}",
+          h
+        )))
+      }
+    };
+    let proxy = proxy_created(factory, &receipt.logs)
+      .ok_or_else(|| Error::Other("no ProxyCreation event in receipt".to_string()))?;
+    if proxy != predicted {
+      return Err(Error::Other(format!(
+        "proxy deployed to {:?}, predicted {:?}",
+        proxy, predicted
+      )));
+    }
+    self.set_address(proxy);
+    Ok(format!("{:?}", proxy))
+  }
+}
@@ -0,0 +1,197 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas budgets for a SafeTransaction, estimated before anyone signs it so the signed
+//! `safeTxGas` and `baseGas` are realistic.
+
+use super::{Operation, SafeClient, SafeTransaction};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  web3::{
+    ethabi::{decode, param_type::ParamType, short_signature, Token},
+    types::{Bytes as Web3Bytes, CallRequest},
+  },
+};
+
+/// Intrinsic cost of any transaction
+const TX_BASE_GAS: u64 = 21_000;
+/// ecrecover plus the owner lookup and ordering checks of `checkNSignatures`
+const SIGNATURE_GAS: u64 = 6_000;
+/// Writing the nonce for the first time, and updating it afterwards
+const FIRST_NONCE_GAS: u64 = 20_000;
+const NONCE_GAS: u64 = 5_000;
+/// Hashing, events and the refund around the inner call
+const EXEC_OVERHEAD_GAS: u64 = 15_000;
+/// Headroom on top of the measured inner call
+const SAFE_TX_GAS_BUFFER: u64 = 10_000;
+
+/// Recommended gas for a SafeTransaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasEstimate {
+  /// Gas forwarded to the inner call
+  pub safe_tx_gas: U256,
+  /// Gas spent outside the inner call, used for the refund when `gasPrice` is set
+  pub base_gas: U256,
+  /// Gas limit for the `execTransaction` transaction itself
+  pub gas_limit: U256,
+}
+
+impl GasEstimate {
+  /// `tx` with this estimate's `safeTxGas` and `baseGas`, ready to be signed
+  pub fn apply(&self, tx: SafeTransaction) -> SafeTransaction {
+    tx.safe_tx_gas(self.safe_tx_gas).base_gas(self.base_gas)
+  }
+}
+
+/// Calldata gas: 4 per zero byte, 16 per non-zero byte
+pub fn data_gas(data: &[u8]) -> u64 {
+  data.iter().map(|b| if *b == 0 { 4 } else { 16 }).sum()
+}
+
+/// The gas `execTransaction` needs left before the inner call (the GS010 check)
+pub fn required_gas_left(safe_tx_gas: U256) -> U256 {
+  let scaled = safe_tx_gas * 64 / 63;
+  let padded = safe_tx_gas + 2500;
+  scaled.max(padded) + 500
+}
+
+/// `execTransaction` calldata for `tx` with `threshold` placeholder signatures
+fn exec_transaction_data(tx: &SafeTransaction, threshold: u64) -> Vec<u8> {
+  utils::encode_call(
+    "execTransaction",
+    &[
+      ParamType::Address,
+      ParamType::Uint(256),
+      ParamType::Bytes,
+      ParamType::Uint(8),
+      ParamType::Uint(256),
+      ParamType::Uint(256),
+      ParamType::Uint(256),
+      ParamType::Address,
+      ParamType::Address,
+      ParamType::Bytes,
+    ],
+    &[
+      Token::Address(tx.to),
+      Token::Uint(tx.value),
+      Token::Bytes(tx.data.clone()),
+      Token::Uint((tx.operation as u8).into()),
+      Token::Uint(tx.safe_tx_gas),
+      Token::Uint(tx.base_gas),
+      Token::Uint(tx.gas_price),
+      Token::Address(tx.gas_token),
+      Token::Address(tx.refund_receiver),
+      Token::Bytes(vec![0xff; threshold as usize * 65]),
+    ],
+  )
+}
+
+/// Decode the gas out of a `requiredTxGas` revert: `Error(string)` wrapping the packed uint256
+fn decode_required_tx_gas(payload: &[u8]) -> Option<U256> {
+  let selector = short_signature("Error", &[ParamType::String]);
+  if payload.len() < 4 || payload[0..4] != selector {
+    return None;
+  }
+  // read as bytes, the packed uint is not valid utf-8
+  match decode(&[ParamType::Bytes], &payload[4..]).ok()?.as_slice() {
+    [Token::Bytes(b)] if b.len() == 32 => Some(U256::from_big_endian(b)),
+    _ => None,
+  }
+}
+
+impl SafeClient {
+  /// Gas used by `tx`'s call when executed by the Safe, through the `requiredTxGas`
+  /// revert on 1.3.0. Errors if the call would fail.
+  pub async fn required_tx_gas(&self, tx: &SafeTransaction) -> Result<U256> {
+    if !self.safe_version().await?.has_required_tx_gas() {
+      return self.estimate_call_gas(tx).await;
+    }
+    let data = utils::encode_call(
+      "requiredTxGas",
+      &[
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Bytes,
+        ParamType::Uint(8),
+      ],
+      &[
+        Token::Address(tx.to),
+        Token::Uint(tx.value),
+        Token::Bytes(tx.data.clone()),
+        Token::Uint((tx.operation as u8).into()),
+      ],
+    );
+    let call = CallRequest {
+      from: Some(self.address),
+      to: Some(self.address),
+      data: Some(Web3Bytes(data)),
+      ..Default::default()
+    };
+    let payload = match self.web3.eth().call(call, None).await {
+      Ok(result) => result.0,
+      Err(e) => utils::revert_data(&e).ok_or(e)?,
+    };
+    decode_required_tx_gas(&payload).ok_or_else(|| {
+      Error::Other(format!(
+        "transaction to {:?} would fail, couldnt estimate safeTxGas",
+        tx.to
+      ))
+    })
+  }
+
+  /// Gas used by `tx`'s call when sent from the Safe's address, for 1.4.x where
+  /// `requiredTxGas` no longer exists. Only calls can be estimated this way.
+  async fn estimate_call_gas(&self, tx: &SafeTransaction) -> Result<U256> {
+    if tx.operation != Operation::Call {
+      return Err(Error::Other(
+        "delegate calls can't be estimated without requiredTxGas".to_string(),
+      ));
+    }
+    let call = CallRequest {
+      from: Some(self.address),
+      to: Some(tx.to),
+      value: Some(tx.value),
+      data: Some(Web3Bytes(tx.data.clone())),
+      ..Default::default()
+    };
+    let gas = self.web3.eth().estimate_gas(call, None).await?;
+    // the Safe doesn't pay the intrinsic cost of the estimated transaction
+    Ok(gas.saturating_sub((TX_BASE_GAS + data_gas(&tx.data)).into()))
+  }
+
+  /// Recommend `safeTxGas`, `baseGas` and the outer gas limit for `tx`, before signing
+  pub async fn estimate_safe_tx_gas(&self, tx: &SafeTransaction) -> Result<GasEstimate> {
+    let required = self.required_tx_gas(tx).await?;
+    let safe_tx_gas = required + SAFE_TX_GAS_BUFFER;
+
+    let threshold = self.get_threshold().await?;
+    let nonce_gas = if tx.nonce.is_zero() {
+      FIRST_NONCE_GAS
+    } else {
+      NONCE_GAS
+    };
+    let calldata = exec_transaction_data(&tx.clone().safe_tx_gas(safe_tx_gas), threshold);
+    let base_gas =
+      TX_BASE_GAS + data_gas(&calldata) + threshold * SIGNATURE_GAS + nonce_gas + EXEC_OVERHEAD_GAS;
+
+    Ok(GasEstimate {
+      safe_tx_gas,
+      base_gas: base_gas.into(),
+      gas_limit: U256::from(base_gas) + required_gas_left(safe_tx_gas),
+    })
+  }
+}
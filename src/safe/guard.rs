@@ -0,0 +1,113 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction guard configuration. 1.3.0's `setGuard` accepts any address, and a guard
+//! that reverts on every `checkTransaction` locks the Safe, so candidates are checked
+//! for the `Guard` interface through ERC-165 first.
+
+use super::{SafeClient, SafeTransaction, GUARD_STORAGE_SLOT};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  web3::{
+    ethabi::{param_type::ParamType, Token},
+    types::{Bytes as Web3Bytes, CallRequest},
+  },
+};
+
+/// `type(Guard).interfaceId`: checkTransaction ^ checkAfterExecution
+pub const GUARD_INTERFACE_ID: [u8; 4] = [0xe6, 0xd7, 0xa8, 0x3a];
+
+/// `type(IERC165).interfaceId`
+pub const ERC165_INTERFACE_ID: [u8; 4] = [0x01, 0xff, 0xc9, 0xa7];
+
+pub fn set_guard_data(guard: Address) -> Vec<u8> {
+  utils::encode_call("setGuard", &[ParamType::Address], &[Token::Address(guard)])
+}
+
+impl SafeClient {
+  /// The current guard, zero if none is set
+  pub async fn get_guard(&self) -> Result<Address> {
+    self
+      .storage_address(
+        U256::from_big_endian(&GUARD_STORAGE_SLOT),
+        BlockNumber::Latest,
+      )
+      .await
+  }
+
+  /// `contract.supportsInterface(interface_id)`, false if the call reverts or returns garbage
+  pub async fn supports_interface(&self, contract: Address, interface_id: [u8; 4]) -> Result<bool> {
+    let data = utils::encode_call(
+      "supportsInterface",
+      &[ParamType::FixedBytes(4)],
+      &[Token::FixedBytes(interface_id.to_vec())],
+    );
+    let call = CallRequest {
+      to: Some(contract),
+      data: Some(Web3Bytes(data)),
+      ..Default::default()
+    };
+    match self.web3.eth().call(call, None).await {
+      Ok(result) => Ok(result.0.len() == 32 && U256::from_big_endian(&result.0) == U256::one()),
+      Err(ethcontract::web3::Error::Rpc(e)) => {
+        log::debug!("supportsInterface reverted: {:?}", e);
+        Ok(false)
+      }
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  /// ERC-165 detection of the `Guard` interface on `guard`
+  pub async fn is_guard(&self, guard: Address) -> Result<bool> {
+    Ok(
+      self.supports_interface(guard, ERC165_INTERFACE_ID).await?
+        && !self.supports_interface(guard, [0xff; 4]).await?
+        && self.supports_interface(guard, GUARD_INTERFACE_ID).await?,
+    )
+  }
+
+  /// A SafeTransaction setting `guard`, refused unless it implements `Guard`
+  pub async fn set_guard_tx(&self, guard: Address) -> Result<SafeTransaction> {
+    if guard == utils::zero_address() {
+      return Err(Error::Other(
+        "use clear_guard_tx to remove the guard".to_string(),
+      ));
+    }
+    if !self.is_guard(guard).await? {
+      return Err(Error::Other(format!(
+        "{:?} does not implement the Guard interface",
+        guard
+      )));
+    }
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(set_guard_data(guard)),
+    )
+  }
+
+  /// A SafeTransaction removing the guard
+  pub async fn clear_guard_tx(&self) -> Result<SafeTransaction> {
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(set_guard_data(utils::zero_address())),
+    )
+  }
+}
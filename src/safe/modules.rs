@@ -0,0 +1,172 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Module management on the Safe, and `ModuleClient` for an enabled module to execute
+//! calls through the Safe without owner signatures.
+
+use super::{
+  decode_log, prev_owner, ExecResult, GnosisSafe, Operation, SafeClient, SafeEvent,
+  SafeTransaction, SENTINEL_ADDRESS,
+};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  transaction::TransactionResult,
+  transport::DynTransport,
+  web3::{
+    ethabi::{param_type::ParamType, Token},
+    types::BlockId,
+  },
+  Bytes,
+};
+
+/// The entry pointing at `module` in the Safe's module linked list, which `disableModule`
+/// takes as `prevModule`. `modules` is in `getModulesPaginated` order.
+pub fn prev_module(modules: &[Address], module: Address) -> Option<Address> {
+  // same layout as the owner list
+  prev_owner(modules, module)
+}
+
+pub fn enable_module_data(module: Address) -> Vec<u8> {
+  utils::encode_call(
+    "enableModule",
+    &[ParamType::Address],
+    &[Token::Address(module)],
+  )
+}
+
+pub fn disable_module_data(prev_module: Address, module: Address) -> Vec<u8> {
+  utils::encode_call(
+    "disableModule",
+    &[ParamType::Address, ParamType::Address],
+    &[Token::Address(prev_module), Token::Address(module)],
+  )
+}
+
+impl SafeClient {
+  /// Every enabled module, in linked list order
+  pub async fn get_modules(&self) -> Result<Vec<Address>> {
+    self.modules_at(BlockId::Number(BlockNumber::Latest)).await
+  }
+
+  pub async fn is_module_enabled(&self, module: Address) -> Result<bool> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let enabled = safe.is_module_enabled(module).call().await?;
+    Ok(enabled)
+  }
+
+  /// A SafeTransaction enabling `module`
+  pub async fn enable_module_tx(&self, module: Address) -> Result<SafeTransaction> {
+    if module == utils::zero_address() || module == SENTINEL_ADDRESS {
+      return Err(Error::Other(format!("{:?} cannot be a module", module)));
+    }
+    if self.is_module_enabled(module).await? {
+      return Err(Error::Other(format!("{:?} is already enabled", module)));
+    }
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(enable_module_data(module)),
+    )
+  }
+
+  /// A SafeTransaction disabling `module`
+  pub async fn disable_module_tx(&self, module: Address) -> Result<SafeTransaction> {
+    let modules = self.get_modules().await?;
+    let prev = prev_module(&modules, module)
+      .ok_or_else(|| Error::Other(format!("{:?} is not enabled", module)))?;
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(disable_module_data(prev, module)),
+    )
+  }
+}
+
+/// Executes calls as the Safe from an enabled module, through `execTransactionFromModule`
+#[derive(Clone)]
+pub struct ModuleClient {
+  web3: Web3<DynTransport>,
+  safe: Address,
+}
+
+impl ModuleClient {
+  pub fn new(web3: &Web3<DynTransport>, safe: Address) -> Self {
+    Self {
+      web3: web3.clone(),
+      safe,
+    }
+  }
+
+  pub fn safe(&self) -> Address {
+    self.safe
+  }
+
+  /// Execute a call from the Safe, sent by the module `module_account`. The inner call
+  /// failing does not revert the transaction, so the receipt is checked for
+  /// `ExecutionFromModuleFailure`.
+  pub async fn exec(
+    &self,
+    module_account: Account<DynTransport>,
+    to: Address,
+    value: U256,
+    data: Vec<u8>,
+    operation: Operation,
+  ) -> Result<ExecResult> {
+    let safe = GnosisSafe::at(&self.web3, self.safe);
+    let module = module_account.address();
+    if !safe.is_module_enabled(module).call().await? {
+      return Err(Error::Other(format!(
+        "{:?} is not an enabled module",
+        module
+      )));
+    }
+    let nonce = self.web3.eth().transaction_count(module, None).await?;
+    let result = safe
+      .exec_transaction_from_module(to, value, Bytes(data), operation as u8)
+      .from(module_account)
+      .nonce(nonce)
+      .send()
+      .await?;
+    if let TransactionResult::Receipt(r) = &result {
+      if r
+        .logs
+        .iter()
+        .filter(|l| l.address == self.safe)
+        .filter_map(decode_log)
+        .any(|e| matches!(e, SafeEvent::ExecutionFromModuleFailure { .. }))
+      {
+        return Err(Error::ModuleExecutionFailure(module, r.transaction_hash));
+      }
+    }
+    Ok(result.into())
+  }
+
+  /// A plain call from the Safe
+  pub async fn exec_call(
+    &self,
+    module_account: Account<DynTransport>,
+    to: Address,
+    value: U256,
+    data: Vec<u8>,
+  ) -> Result<ExecResult> {
+    self
+      .exec(module_account, to, value, data, Operation::Call)
+      .await
+  }
+}
@@ -0,0 +1,150 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Batching several calls into one Safe transaction through the canonical `MultiSend`
+//! library, which the Safe must `DelegateCall` so every call is made by the Safe itself.
+
+use super::{Operation, SafeClient, SafeTransaction};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  web3::ethabi::{decode, param_type::ParamType, short_signature, Token},
+};
+
+/// One call of a MultiSend batch
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiSendCall {
+  pub operation: Operation,
+  pub to: Address,
+  pub value: U256,
+  pub data: Vec<u8>,
+}
+
+impl MultiSendCall {
+  /// A plain call made by the Safe
+  pub fn call(to: Address, value: U256, data: Vec<u8>) -> Self {
+    Self {
+      operation: Operation::Call,
+      to,
+      value,
+      data,
+    }
+  }
+}
+
+/// The packed `transactions` argument of `multiSend`: for every call
+/// `operation (1) || to (20) || value (32) || data length (32) || data`
+pub fn encode_multi_send(calls: &[MultiSendCall]) -> Vec<u8> {
+  let mut packed = Vec::new();
+  for call in calls {
+    let mut word = [0u8; 32];
+    packed.push(call.operation as u8);
+    packed.extend_from_slice(call.to.as_bytes());
+    call.value.to_big_endian(&mut word);
+    packed.extend_from_slice(&word);
+    U256::from(call.data.len()).to_big_endian(&mut word);
+    packed.extend_from_slice(&word);
+    packed.extend_from_slice(&call.data);
+  }
+  packed
+}
+
+/// The `multiSend(bytes)` calldata for a batch
+pub fn multi_send_data(calls: &[MultiSendCall]) -> Vec<u8> {
+  utils::encode_call(
+    "multiSend",
+    &[ParamType::Bytes],
+    &[Token::Bytes(encode_multi_send(calls))],
+  )
+}
+
+/// Split a packed `transactions` argument back into its calls
+pub fn decode_multi_send(packed: &[u8]) -> Result<Vec<MultiSendCall>> {
+  let mut calls = Vec::new();
+  let mut i = 0;
+  while i < packed.len() {
+    if packed.len() - i < 85 {
+      return Err(Error::Other(format!(
+        "truncated multisend entry at byte {}",
+        i
+      )));
+    }
+    let operation = match packed[i] {
+      0 => Operation::Call,
+      1 => Operation::DelegateCall,
+      op => {
+        return Err(Error::Other(format!(
+          "invalid multisend operation {} at byte {}",
+          op, i
+        )))
+      }
+    };
+    let to = H160::from_slice(&packed[i + 1..i + 21]);
+    let value = U256::from_big_endian(&packed[i + 21..i + 53]);
+    let length = U256::from_big_endian(&packed[i + 53..i + 85]);
+    let start = i + 85;
+    if length > U256::from(packed.len() - start) {
+      return Err(Error::Other(format!(
+        "multisend entry at byte {} overruns the payload",
+        i
+      )));
+    }
+    let end = start + length.as_usize();
+    calls.push(MultiSendCall {
+      operation,
+      to,
+      value,
+      data: packed[start..end].to_vec(),
+    });
+    i = end;
+  }
+  Ok(calls)
+}
+
+/// Decode `multiSend(bytes)` calldata, e.g. the data of a SafeTransaction up for review
+pub fn decode_multi_send_data(data: &[u8]) -> Result<Vec<MultiSendCall>> {
+  let selector = short_signature("multiSend", &[ParamType::Bytes]);
+  if data.len() < 4 || data[0..4] != selector {
+    return Err(Error::Other("not a multiSend call".to_string()));
+  }
+  let tokens = decode(&[ParamType::Bytes], &data[4..])
+    .map_err(|e| Error::Other(format!("couldnt decode multiSend: {}", e)))?;
+  match tokens.into_iter().next() {
+    Some(Token::Bytes(packed)) => decode_multi_send(&packed),
+    _ => Err(Error::Other("couldnt decode multiSend".to_string())),
+  }
+}
+
+impl SafeClient {
+  /// A SafeTransaction delegatecalling `multi_send` to run `calls` in order, all or
+  /// nothing, with one nonce and one set of signatures
+  pub async fn multi_send_tx(
+    &self,
+    multi_send: Address,
+    calls: &[MultiSendCall],
+  ) -> Result<SafeTransaction> {
+    if calls.is_empty() {
+      return Err(Error::Other("empty multisend batch".to_string()));
+    }
+    Ok(
+      self
+        .new_transaction(multi_send)
+        .await?
+        .operation(Operation::DelegateCall)
+        .data(multi_send_data(calls)),
+    )
+  }
+}
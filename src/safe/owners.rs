@@ -0,0 +1,203 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Owner management: calldata for the `OwnerManager` functions and SafeTransactions
+//! that call them on the Safe itself, validated against the current owner set.
+
+use super::{SafeClient, SafeSignatures, SafeTransaction, SENTINEL_ADDRESS};
+use crate::{error::Error, safe::ExecResult, utils, Result};
+use ethcontract::{
+  prelude::*,
+  transport::DynTransport,
+  web3::ethabi::{param_type::ParamType, Token},
+};
+
+/// The entry pointing at `owner` in the Safe's owner linked list, which `removeOwner`
+/// and `swapOwner` take as `prevOwner`. `owners` is in `getOwners()` order.
+pub fn prev_owner(owners: &[Address], owner: Address) -> Option<Address> {
+  let index = owners.iter().position(|o| *o == owner)?;
+  if index == 0 {
+    Some(SENTINEL_ADDRESS)
+  } else {
+    Some(owners[index - 1])
+  }
+}
+
+pub fn add_owner_with_threshold_data(owner: Address, threshold: u64) -> Vec<u8> {
+  utils::encode_call(
+    "addOwnerWithThreshold",
+    &[ParamType::Address, ParamType::Uint(256)],
+    &[Token::Address(owner), Token::Uint(threshold.into())],
+  )
+}
+
+pub fn remove_owner_data(prev_owner: Address, owner: Address, threshold: u64) -> Vec<u8> {
+  utils::encode_call(
+    "removeOwner",
+    &[ParamType::Address, ParamType::Address, ParamType::Uint(256)],
+    &[
+      Token::Address(prev_owner),
+      Token::Address(owner),
+      Token::Uint(threshold.into()),
+    ],
+  )
+}
+
+pub fn swap_owner_data(prev_owner: Address, old_owner: Address, new_owner: Address) -> Vec<u8> {
+  utils::encode_call(
+    "swapOwner",
+    &[ParamType::Address, ParamType::Address, ParamType::Address],
+    &[
+      Token::Address(prev_owner),
+      Token::Address(old_owner),
+      Token::Address(new_owner),
+    ],
+  )
+}
+
+pub fn change_threshold_data(threshold: u64) -> Vec<u8> {
+  utils::encode_call(
+    "changeThreshold",
+    &[ParamType::Uint(256)],
+    &[Token::Uint(threshold.into())],
+  )
+}
+
+fn check_threshold(threshold: u64, owner_count: usize) -> Result<()> {
+  if threshold == 0 || threshold > owner_count as u64 {
+    return Err(Error::Other(format!(
+      "threshold {} is invalid for {} owners",
+      threshold, owner_count
+    )));
+  }
+  Ok(())
+}
+
+impl SafeClient {
+  fn check_new_owner(&self, owners: &[Address], owner: Address) -> Result<()> {
+    if owner == utils::zero_address() || owner == SENTINEL_ADDRESS || owner == self.address {
+      return Err(Error::Other(format!("{:?} cannot be an owner", owner)));
+    }
+    if owners.contains(&owner) {
+      return Err(Error::Other(format!("{:?} is already an owner", owner)));
+    }
+    Ok(())
+  }
+
+  /// A SafeTransaction adding `owner` and setting the threshold to `threshold`
+  pub async fn add_owner_tx(&self, owner: Address, threshold: u64) -> Result<SafeTransaction> {
+    let owners = self.get_owners().await?;
+    self.check_new_owner(&owners, owner)?;
+    check_threshold(threshold, owners.len() + 1)?;
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(add_owner_with_threshold_data(owner, threshold)),
+    )
+  }
+
+  /// A SafeTransaction removing `owner` and setting the threshold to `threshold`
+  pub async fn remove_owner_tx(&self, owner: Address, threshold: u64) -> Result<SafeTransaction> {
+    let owners = self.get_owners().await?;
+    let prev = prev_owner(&owners, owner)
+      .ok_or_else(|| Error::Other(format!("{:?} is not an owner", owner)))?;
+    check_threshold(threshold, owners.len() - 1)?;
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(remove_owner_data(prev, owner, threshold)),
+    )
+  }
+
+  /// A SafeTransaction replacing `old_owner` with `new_owner`, keeping the threshold
+  pub async fn swap_owner_tx(
+    &self,
+    old_owner: Address,
+    new_owner: Address,
+  ) -> Result<SafeTransaction> {
+    let owners = self.get_owners().await?;
+    let prev = prev_owner(&owners, old_owner)
+      .ok_or_else(|| Error::Other(format!("{:?} is not an owner", old_owner)))?;
+    self.check_new_owner(&owners, new_owner)?;
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(swap_owner_data(prev, old_owner, new_owner)),
+    )
+  }
+
+  /// A SafeTransaction changing the threshold
+  pub async fn change_threshold_tx(&self, threshold: u64) -> Result<SafeTransaction> {
+    let owners = self.get_owners().await?;
+    check_threshold(threshold, owners.len())?;
+    Ok(
+      self
+        .new_transaction(self.address)
+        .await?
+        .data(change_threshold_data(threshold)),
+    )
+  }
+
+  pub async fn add_owner(
+    &self,
+    from_account: Account<DynTransport>,
+    owner: Address,
+    threshold: u64,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let tx = self.add_owner_tx(owner, threshold).await?;
+    self.exec_signed(from_account, &tx, signatures, gas).await
+  }
+
+  pub async fn remove_owner(
+    &self,
+    from_account: Account<DynTransport>,
+    owner: Address,
+    threshold: u64,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let tx = self.remove_owner_tx(owner, threshold).await?;
+    self.exec_signed(from_account, &tx, signatures, gas).await
+  }
+
+  pub async fn swap_owner(
+    &self,
+    from_account: Account<DynTransport>,
+    old_owner: Address,
+    new_owner: Address,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let tx = self.swap_owner_tx(old_owner, new_owner).await?;
+    self.exec_signed(from_account, &tx, signatures, gas).await
+  }
+
+  pub async fn change_threshold(
+    &self,
+    from_account: Account<DynTransport>,
+    threshold: u64,
+    signatures: &SafeSignatures,
+    gas: Option<u128>,
+  ) -> Result<ExecResult> {
+    let tx = self.change_threshold_tx(threshold).await?;
+    self.exec_signed(from_account, &tx, signatures, gas).await
+  }
+}
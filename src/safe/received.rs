@@ -0,0 +1,155 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ETH arriving at the Safe (`SafeReceived`), paired with the Router `Deposit` it came
+//! in through. ETH deposits are forwarded to the router owner, so anything received
+//! without a matching deposit bypassed the Router.
+
+use super::{GnosisSafe, SafeClient, SafeEvent};
+use crate::{
+  router::{DepositEvent, RouterClient, Sender},
+  utils, Result,
+};
+use ethcontract::prelude::*;
+use futures::{sink::SinkExt, stream::StreamExt};
+
+#[derive(Debug, Clone)]
+pub struct ReceivedEvent {
+  pub sender: Address,
+  pub value: U256,
+  pub tx_hash: Vec<u8>,
+  pub log_index: usize,
+  pub blockheight: u64,
+  pub confirmations: u64,
+  /// The Router deposit this ETH was forwarded from, if any
+  pub deposit: Option<DepositEvent>,
+}
+
+impl ReceivedEvent {
+  pub fn is_router_deposit(&self) -> bool {
+    self.deposit.is_some()
+  }
+}
+
+/// Attach to each received event the ETH `Deposit` of `router` from the same transaction
+/// with the same amount. Each deposit is matched at most once.
+pub fn correlate_deposits(
+  router: Address,
+  received: &mut [ReceivedEvent],
+  deposits: &[DepositEvent],
+) {
+  let mut used = vec![false; deposits.len()];
+  for r in received.iter_mut().filter(|r| r.sender == router) {
+    let found = deposits.iter().enumerate().find(|(i, d)| {
+      !used[*i]
+        && d.asset == utils::zero_address()
+        && d.tx_hash == r.tx_hash
+        && U256::from(d.amount) == r.value
+    });
+    if let Some((i, d)) = found {
+      used[i] = true;
+      r.deposit = Some(d.clone());
+    }
+  }
+}
+
+impl SafeClient {
+  /// Every `SafeReceived` since `since` (or ever), without Router correlation
+  pub async fn get_received(&self, since: Option<u64>) -> Result<Vec<ReceivedEvent>> {
+    let bn = self.web3.eth().block_number().await?.as_u64();
+    let events = self.get_events(since).await?;
+    Ok(
+      events
+        .into_iter()
+        .filter_map(|e| match e.event {
+          SafeEvent::SafeReceived { sender, value } => Some(ReceivedEvent {
+            sender,
+            value,
+            tx_hash: e.tx_hash,
+            log_index: e.log_index,
+            blockheight: e.block_number,
+            confirmations: bn.saturating_sub(e.block_number),
+            deposit: None,
+          }),
+          _ => None,
+        })
+        .collect(),
+    )
+  }
+
+  /// Every `SafeReceived` since `since`, each with the Router deposit it came from
+  pub async fn get_received_with_deposits(
+    &self,
+    router: &RouterClient,
+    since: Option<u64>,
+  ) -> Result<Vec<ReceivedEvent>> {
+    let mut received = self.get_received(since).await?;
+    let deposits = router
+      .get_deposits_by_asset(utils::zero_address(), since)
+      .await?;
+    correlate_deposits(router.address(), &mut received, &deposits);
+    Ok(received)
+  }
+
+  /// Stream `SafeReceived` events as they are mined, correlated with `router` deposits
+  pub async fn subscribe_received(&self, router: &RouterClient, mut tx: Sender<ReceivedEvent>) {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let mut received = safe.events().safe_received().stream().boxed();
+    while let Some(r_res) = received.next().await {
+      let r = match r_res {
+        Ok(r) => r,
+        Err(e) => {
+          log::warn!("SafeReceived stream error: {:?}", e);
+          continue;
+        }
+      };
+      let (data, meta) = match (r.clone().added(), r.meta) {
+        (Some(data), Some(meta)) => (data, meta),
+        _ => continue,
+      };
+      let bn = self
+        .web3
+        .eth()
+        .block_number()
+        .await
+        .map(|n| n.as_u64())
+        .unwrap_or(meta.block_number);
+      let mut event = ReceivedEvent {
+        sender: data.sender,
+        value: data.value,
+        tx_hash: meta.transaction_hash.as_bytes().to_vec(),
+        log_index: meta.log_index,
+        blockheight: meta.block_number,
+        confirmations: bn.saturating_sub(meta.block_number),
+        deposit: None,
+      };
+      if data.sender == router.address() {
+        match router
+          .get_deposits_by_asset(utils::zero_address(), Some(meta.block_number))
+          .await
+        {
+          Ok(deposits) => correlate_deposits(
+            router.address(),
+            std::slice::from_mut(&mut event),
+            &deposits,
+          ),
+          Err(e) => log::warn!("couldnt get router deposits: {:?}", e),
+        }
+      }
+      tx.send(event).await.expect("COULDNT SEND RECEIVED EVENT");
+    }
+  }
+}
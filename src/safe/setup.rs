@@ -0,0 +1,157 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The `GnosisSafe.setup` initializer, so a proxy can be created and configured in the
+//! same transaction instead of leaving `setup` open to whoever calls it first.
+
+use super::{SafeClient, SENTINEL_ADDRESS};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  transport::DynTransport,
+  web3::ethabi::{param_type::ParamType, Token},
+};
+
+/// Every argument of `GnosisSafe.setup`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeSetup {
+  pub owners: Vec<Address>,
+  pub threshold: u64,
+  /// Contract delegatecalled with `data` during setup, e.g. to enable modules
+  pub to: Address,
+  pub data: Vec<u8>,
+  pub fallback_handler: Address,
+  pub payment_token: Address,
+  pub payment: U256,
+  pub payment_receiver: Address,
+}
+
+impl SafeSetup {
+  /// Owners and threshold only: no setup call, fallback handler or payment
+  pub fn new(owners: Vec<Address>, threshold: u64) -> Self {
+    Self {
+      owners,
+      threshold,
+      to: utils::zero_address(),
+      data: Vec::new(),
+      fallback_handler: utils::zero_address(),
+      payment_token: utils::zero_address(),
+      payment: U256::zero(),
+      payment_receiver: utils::zero_address(),
+    }
+  }
+
+  pub fn delegate_call(mut self, to: Address, data: Vec<u8>) -> Self {
+    self.to = to;
+    self.data = data;
+    self
+  }
+
+  pub fn fallback_handler(mut self, fallback_handler: Address) -> Self {
+    self.fallback_handler = fallback_handler;
+    self
+  }
+
+  /// Refund `payment` of `payment_token` (zero for ETH) to `payment_receiver`
+  /// (zero for tx.origin) from the new Safe
+  pub fn payment(
+    mut self,
+    payment_token: Address,
+    payment: U256,
+    payment_receiver: Address,
+  ) -> Self {
+    self.payment_token = payment_token;
+    self.payment = payment;
+    self.payment_receiver = payment_receiver;
+    self
+  }
+
+  /// The checks `setupOwners` makes, so a bad configuration fails before deploying
+  pub fn validate(&self) -> Result<()> {
+    if self.threshold == 0 || self.threshold > self.owners.len() as u64 {
+      return Err(Error::Other(format!(
+        "threshold {} is invalid for {} owners",
+        self.threshold,
+        self.owners.len()
+      )));
+    }
+    for (i, owner) in self.owners.iter().enumerate() {
+      if *owner == utils::zero_address() || *owner == SENTINEL_ADDRESS {
+        return Err(Error::Other(format!("{:?} cannot be an owner", owner)));
+      }
+      if self.owners[..i].contains(owner) {
+        return Err(Error::Other(format!("duplicate owner {:?}", owner)));
+      }
+    }
+    Ok(())
+  }
+
+  /// The `setup` calldata, used as the proxy factory's initializer
+  pub fn encode(&self) -> Vec<u8> {
+    utils::encode_call(
+      "setup",
+      &[
+        ParamType::Array(Box::new(ParamType::Address)),
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Bytes,
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Address,
+      ],
+      &[
+        Token::Array(self.owners.iter().map(|o| Token::Address(*o)).collect()),
+        Token::Uint(self.threshold.into()),
+        Token::Address(self.to),
+        Token::Bytes(self.data.clone()),
+        Token::Address(self.fallback_handler),
+        Token::Address(self.payment_token),
+        Token::Uint(self.payment),
+        Token::Address(self.payment_receiver),
+      ],
+    )
+  }
+}
+
+impl SafeClient {
+  /// The address `deploy_and_setup` will create the Safe at, computed offline
+  pub fn predict_setup_address(
+    &self,
+    factory: Address,
+    singleton: Address,
+    setup: &SafeSetup,
+    salt_nonce: U256,
+  ) -> Result<Address> {
+    self.predict_address(factory, singleton, &setup.encode(), salt_nonce)
+  }
+
+  /// Create a proxy through `factory` that runs `setup` as its initializer, so the Safe
+  /// never exists unconfigured
+  pub async fn deploy_and_setup(
+    &mut self,
+    account: Account<DynTransport>,
+    factory: Address,
+    singleton: Address,
+    setup: &SafeSetup,
+    salt_nonce: U256,
+  ) -> Result<String> {
+    setup.validate()?;
+    self
+      .deploy_with_proxy_factory(account, factory, singleton, setup.encode(), salt_nonce)
+      .await
+  }
+}
@@ -0,0 +1,513 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{error::Error, safe::eip712, Result};
+use ethcontract::{prelude::*, web3::signing::Key};
+use std::collections::BTreeMap;
+
+/// How `checkNSignatures` validates a signature, selected by its `v` byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureType {
+  /// ECDSA over the SafeTx hash, `v` is 27 or 28
+  Ecdsa,
+  /// `v` is 1, the owner called `approveHash` or is the executor
+  ApprovedHash,
+  /// `v` is 31 or 32, ECDSA over the EIP-191 prefixed SafeTx hash (`eth_sign` / `personal_sign`)
+  EthSign,
+  /// `v` is 0, an EIP-1271 contract owner (such as another Safe) validates
+  /// the signature in `isValidSignature`
+  Contract,
+}
+
+/// A signature over a SafeTx hash, in the 65 byte `r, s, v` layout `checkNSignatures` reads.
+/// Contract signatures also carry `dynamic`, the bytes passed to `isValidSignature`,
+/// which are appended after all the static parts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeSignature {
+  pub owner: Address,
+  pub kind: SignatureType,
+  pub data: Vec<u8>,
+  pub dynamic: Vec<u8>,
+}
+
+/// The pre-validated entry for an owner who approved the hash on chain:
+/// `r` is the owner, `s` is zero and `v` is 1
+pub fn approved_hash_signature(owner: Address) -> Vec<u8> {
+  let mut data = vec![0u8; 65];
+  data[12..32].copy_from_slice(owner.as_bytes());
+  data[64] = 1;
+  data
+}
+
+/// The static part of a contract signature: `r` is the owner, `s` the offset of
+/// the dynamic part within the packed signatures and `v` is 0
+pub fn contract_signature(owner: Address, offset: usize) -> Vec<u8> {
+  let mut data = vec![0u8; 65];
+  data[12..32].copy_from_slice(owner.as_bytes());
+  U256::from(offset).to_big_endian(&mut data[32..64]);
+  data
+}
+
+/// Recover the address that produced an ECDSA `r, s, v` signature over `hash`
+pub fn recover(hash: &[u8; 32], signature: &[u8]) -> Result<Address> {
+  if signature.len() != 65 {
+    return Err(Error::InvalidSignature(format!(
+      "expected 65 bytes, got {}",
+      signature.len()
+    )));
+  }
+  let v = signature[64];
+  if v != 27 && v != 28 {
+    return Err(Error::InvalidSignature(format!("unsupported v {}", v)));
+  }
+  let sig = libsecp256k1::Signature::parse_standard_slice(&signature[..64])
+    .map_err(|e| Error::InvalidSignature(format!("{:?}", e)))?;
+  let recovery_id = libsecp256k1::RecoveryId::parse(v - 27)
+    .map_err(|e| Error::InvalidSignature(format!("{:?}", e)))?;
+  let public_key = libsecp256k1::recover(&libsecp256k1::Message::parse(hash), &sig, &recovery_id)
+    .map_err(|e| Error::InvalidSignature(format!("{:?}", e)))?;
+  let public_key = public_key.serialize();
+  let hash = eip712::keccak256(&public_key[1..]);
+  Ok(Address::from_slice(&hash[12..]))
+}
+
+/// `keccak256("\x19Ethereum Signed Message:\n32" || hash)`, what `eth_sign` and
+/// `personal_sign` actually sign when given the SafeTx hash
+pub fn eth_sign_hash(hash: &[u8; 32]) -> [u8; 32] {
+  let mut message = b"\x19Ethereum Signed Message:\n32".to_vec();
+  message.extend_from_slice(hash);
+  eip712::keccak256(&message)
+}
+
+/// Recover the signer of a Safe `eth_sign` signature (`v` of 31 or 32) over the SafeTx `hash`
+pub fn recover_eth_sign(hash: &[u8; 32], signature: &[u8]) -> Result<Address> {
+  if signature.len() != 65 {
+    return Err(Error::InvalidSignature(format!(
+      "expected 65 bytes, got {}",
+      signature.len()
+    )));
+  }
+  let v = signature[64];
+  if v != 31 && v != 32 {
+    return Err(Error::InvalidSignature(format!("unsupported v {}", v)));
+  }
+  let mut adjusted = signature.to_vec();
+  adjusted[64] = v - 4;
+  recover(&eth_sign_hash(hash), &adjusted)
+}
+
+/// Sign the SafeTx `hash` directly, giving a `v` of 27 or 28
+pub fn sign_hash<K: Key>(key: K, hash: &[u8; 32]) -> Result<Vec<u8>> {
+  let signature = key
+    .sign(hash, None)
+    .map_err(|e| Error::InvalidSignature(format!("{:?}", e)))?;
+  let mut data = signature.r.as_bytes().to_vec();
+  data.extend_from_slice(signature.s.as_bytes());
+  data.push(signature.v as u8);
+  Ok(data)
+}
+
+/// Sign the SafeTx `hash` the way `personal_sign` does, and shift `v` by 4 so the
+/// Safe knows to apply the prefix when recovering
+pub fn sign_eth_sign<K: Key>(key: K, hash: &[u8; 32]) -> Result<Vec<u8>> {
+  let mut data = sign_hash(key, &eth_sign_hash(hash))?;
+  data[64] += 4;
+  Ok(data)
+}
+
+/// Collects owner signatures for one SafeTx hash, in any order, and packs them
+/// sorted by owner address once the threshold is met.
+#[derive(Clone, Debug)]
+pub struct SafeSignatures {
+  hash: [u8; 32],
+  owners: Vec<Address>,
+  threshold: u64,
+  signatures: BTreeMap<Address, SafeSignature>,
+}
+
+impl SafeSignatures {
+  pub fn new(hash: [u8; 32], owners: Vec<Address>, threshold: u64) -> Self {
+    Self {
+      hash,
+      owners,
+      threshold,
+      signatures: BTreeMap::new(),
+    }
+  }
+
+  pub fn hash(&self) -> [u8; 32] {
+    self.hash
+  }
+
+  pub fn threshold(&self) -> u64 {
+    self.threshold
+  }
+
+  /// Add a 65 byte ECDSA signature over the SafeTx hash, returning the owner who signed it
+  pub fn add(&mut self, signature: &[u8]) -> Result<Address> {
+    let owner = recover(&self.hash, signature)?;
+    self.insert(SafeSignature {
+      owner,
+      kind: SignatureType::Ecdsa,
+      data: signature.to_vec(),
+      dynamic: Vec::new(),
+    })?;
+    Ok(owner)
+  }
+
+  /// Add a signature from a wallet that can only `personal_sign` the SafeTx hash.
+  /// Takes the wallet's output as is (`v` of 27 or 28) or already shifted (31 or 32).
+  pub fn add_eth_sign(&mut self, signature: &[u8]) -> Result<Address> {
+    let mut data = signature.to_vec();
+    if data.len() == 65 && (data[64] == 27 || data[64] == 28) {
+      data[64] += 4;
+    }
+    let owner = recover_eth_sign(&self.hash, &data)?;
+    self.insert(SafeSignature {
+      owner,
+      kind: SignatureType::EthSign,
+      data,
+      dynamic: Vec::new(),
+    })?;
+    Ok(owner)
+  }
+
+  /// Add an owner who approved the hash with `approveHash`, or who will send
+  /// the `execTransaction` themselves
+  pub fn add_approved_hash(&mut self, owner: Address) -> Result<()> {
+    self.insert(SafeSignature {
+      owner,
+      kind: SignatureType::ApprovedHash,
+      data: approved_hash_signature(owner),
+      dynamic: Vec::new(),
+    })
+  }
+
+  /// Add the signature of a contract owner, validated by `owner.isValidSignature`.
+  /// The offset is filled in when the set is encoded.
+  pub fn add_contract_signature(&mut self, owner: Address, signature: Vec<u8>) -> Result<()> {
+    self.insert(SafeSignature {
+      owner,
+      kind: SignatureType::Contract,
+      data: contract_signature(owner, 0),
+      dynamic: signature,
+    })
+  }
+
+  pub fn signatures(&self) -> Vec<SafeSignature> {
+    self.signatures.values().cloned().collect()
+  }
+
+  fn insert(&mut self, signature: SafeSignature) -> Result<()> {
+    if !self.owners.contains(&signature.owner) {
+      return Err(Error::InvalidSignature(format!(
+        "{:?} is not an owner of the safe",
+        signature.owner
+      )));
+    }
+    if self.signatures.contains_key(&signature.owner) {
+      return Err(Error::InvalidSignature(format!(
+        "{:?} already signed",
+        signature.owner
+      )));
+    }
+    self.signatures.insert(signature.owner, signature);
+    Ok(())
+  }
+
+  /// Owners who have signed, in ascending address order
+  pub fn signers(&self) -> Vec<Address> {
+    self.signatures.keys().cloned().collect()
+  }
+
+  pub fn len(&self) -> usize {
+    self.signatures.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.signatures.is_empty()
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.signatures.len() as u64 >= self.threshold
+  }
+
+  /// The packed signatures for `execTransaction`, sorted by owner as the Safe requires
+  pub fn encode(&self) -> Result<Vec<u8>> {
+    if !self.is_complete() {
+      return Err(Error::ThresholdNotMet(
+        self.signatures.len(),
+        self.threshold,
+      ));
+    }
+    let mut packed = Vec::with_capacity(self.signatures.len() * 65);
+    let mut dynamic = vec![];
+    for signature in self.signatures.values() {
+      if signature.kind == SignatureType::Contract {
+        // the offset counts from the start of the signatures, past every static part
+        let offset = self.signatures.len() * 65 + dynamic.len();
+        packed.extend(contract_signature(signature.owner, offset));
+        let mut length = [0u8; 32];
+        U256::from(signature.dynamic.len()).to_big_endian(&mut length);
+        dynamic.extend_from_slice(&length);
+        dynamic.extend_from_slice(&signature.dynamic);
+      } else {
+        packed.extend_from_slice(&signature.data);
+      }
+    }
+    packed.extend(dynamic);
+    Ok(packed)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignatureStatus {
+  Valid,
+  /// The signature could not be recovered
+  Invalid(String),
+  /// The signature recovers to an address that is not an owner
+  NotOwner,
+  /// The owner is not strictly greater than the previous one, so the Safe reverts with GS026
+  OutOfOrder,
+  /// A `v = 1` entry for an owner that has not approved the hash and is not the executor
+  NotApproved,
+  /// A `v = 0` entry whose contract did not return the EIP-1271 magic value
+  ContractRejected,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureCheck {
+  pub index: usize,
+  pub owner: Option<Address>,
+  pub kind: Option<SignatureType>,
+  pub status: SignatureStatus,
+}
+
+/// The outcome of running `GnosisSafe.checkNSignatures` locally
+#[derive(Clone, Debug)]
+pub struct SignatureReport {
+  pub hash: [u8; 32],
+  pub threshold: u64,
+  pub checks: Vec<SignatureCheck>,
+  /// Owners without a valid signature in the set
+  pub unsigned_owners: Vec<Address>,
+}
+
+impl SignatureReport {
+  pub fn valid_count(&self) -> usize {
+    self
+      .checks
+      .iter()
+      .filter(|c| c.status == SignatureStatus::Valid)
+      .count()
+  }
+
+  /// How many more valid signatures are needed to reach the threshold
+  pub fn missing(&self) -> u64 {
+    self.threshold.saturating_sub(self.valid_count() as u64)
+  }
+
+  /// Whether `execTransaction` would accept the signatures. Like the contract,
+  /// only the first `threshold` signatures are looked at.
+  pub fn is_valid(&self) -> bool {
+    self.threshold > 0
+      && self.checks.len() as u64 >= self.threshold
+      && self
+        .checks
+        .iter()
+        .take(self.threshold as usize)
+        .all(|c| c.status == SignatureStatus::Valid)
+  }
+}
+
+/// The bytes a `v = 0` entry points at, with the bounds checks of GS021 to GS023
+pub fn contract_signature_data(
+  signatures: &[u8],
+  offset: U256,
+  threshold: u64,
+) -> std::result::Result<Vec<u8>, String> {
+  if offset < U256::from(threshold) * 65 {
+    return Err("GS021: contract signature points inside the static part".to_string());
+  }
+  let end = offset.saturating_add(32.into());
+  if end > U256::from(signatures.len()) {
+    return Err("GS022: contract signature offset out of bounds".to_string());
+  }
+  let start = offset.as_usize();
+  let length = U256::from_big_endian(&signatures[start..start + 32]);
+  if end.saturating_add(length) > U256::from(signatures.len()) {
+    return Err("GS023: contract signature length out of bounds".to_string());
+  }
+  let length = length.as_usize();
+  Ok(signatures[start + 32..start + 32 + length].to_vec())
+}
+
+/// One 65 byte static entry of packed signatures. For `v = 0` entries `contract_data`
+/// is the dynamic part its offset points at, or why the offset is out of bounds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PackedSignature {
+  pub index: usize,
+  pub data: Vec<u8>,
+  pub contract_data: Option<std::result::Result<Vec<u8>, String>>,
+}
+
+impl PackedSignature {
+  pub fn v(&self) -> u8 {
+    self.data[64]
+  }
+
+  /// The owner in `r`, as used by `v = 0` and `v = 1` entries
+  pub fn r_owner(&self) -> Address {
+    Address::from_slice(&self.data[12..32])
+  }
+}
+
+/// Split packed signatures into their static entries the way `checkNSignatures` walks them.
+/// The static parts end where the first dynamic part starts.
+pub fn unpack_signatures(signatures: &[u8], threshold: u64) -> Vec<PackedSignature> {
+  let mut entries = vec![];
+  let mut static_end = signatures.len();
+  let mut index = 0;
+  while (index + 1) * 65 <= static_end {
+    let data = signatures[index * 65..(index + 1) * 65].to_vec();
+    let contract_data = if data[64] == 0 {
+      let offset = U256::from_big_endian(&data[32..64]);
+      if offset < U256::from(static_end) {
+        static_end = offset.as_usize();
+      }
+      Some(contract_signature_data(signatures, offset, threshold))
+    } else {
+      None
+    };
+    entries.push(PackedSignature {
+      index,
+      data,
+      contract_data,
+    });
+    index += 1;
+  }
+  entries
+}
+
+/// Check packed signatures against an owner set the same way `checkNSignatures` does,
+/// but report on every signature instead of reverting at the first bad one.
+/// The entries that can only be checked on chain pass if their owner is listed:
+/// `approved` for `v = 1` (`approvedHashes[owner][hash] != 0` or the executor), and
+/// `contract_valid` for `v = 0` (`isValidSignature` returned the magic value).
+pub fn check_signatures(
+  hash: [u8; 32],
+  owners: &[Address],
+  threshold: u64,
+  signatures: &[u8],
+  approved: &[Address],
+  contract_valid: &[Address],
+) -> SignatureReport {
+  let entries = unpack_signatures(signatures, threshold);
+  check_unpacked_signatures(hash, owners, threshold, &entries, approved, contract_valid)
+}
+
+/// `check_signatures` over entries already split by `unpack_signatures`
+pub fn check_unpacked_signatures(
+  hash: [u8; 32],
+  owners: &[Address],
+  threshold: u64,
+  entries: &[PackedSignature],
+  approved: &[Address],
+  contract_valid: &[Address],
+) -> SignatureReport {
+  let mut checks = vec![];
+  let mut signed = vec![];
+  let mut last_owner = Address::zero();
+  for entry in entries {
+    let index = entry.index;
+    let signature = &entry.data[..];
+    let r_owner = entry.r_owner();
+    let (kind, recovered) = match entry.v() {
+      0 => {
+        let result = match &entry.contract_data {
+          Some(Ok(_)) if contract_valid.contains(&r_owner) => Ok(r_owner),
+          Some(Err(e)) => Err(SignatureStatus::Invalid(e.clone())),
+          _ => Err(SignatureStatus::ContractRejected),
+        };
+        (Some(SignatureType::Contract), result)
+      }
+      1 => {
+        if approved.contains(&r_owner) {
+          (Some(SignatureType::ApprovedHash), Ok(r_owner))
+        } else {
+          (
+            Some(SignatureType::ApprovedHash),
+            Err(SignatureStatus::NotApproved),
+          )
+        }
+      }
+      27 | 28 => (
+        Some(SignatureType::Ecdsa),
+        recover(&hash, signature).map_err(|e| SignatureStatus::Invalid(e.to_string())),
+      ),
+      v if v > 30 => (
+        Some(SignatureType::EthSign),
+        recover_eth_sign(&hash, signature).map_err(|e| SignatureStatus::Invalid(e.to_string())),
+      ),
+      v => (
+        None,
+        Err(SignatureStatus::Invalid(format!(
+          "unsupported signature type v = {}",
+          v
+        ))),
+      ),
+    };
+    let check = match recovered {
+      Ok(owner) => {
+        let status = if !owners.contains(&owner) {
+          SignatureStatus::NotOwner
+        } else if owner <= last_owner {
+          SignatureStatus::OutOfOrder
+        } else {
+          last_owner = owner;
+          signed.push(owner);
+          SignatureStatus::Valid
+        };
+        SignatureCheck {
+          index,
+          owner: Some(owner),
+          kind,
+          status,
+        }
+      }
+      Err(status) => SignatureCheck {
+        index,
+        owner: match kind {
+          Some(SignatureType::ApprovedHash) | Some(SignatureType::Contract) => Some(r_owner),
+          _ => None,
+        },
+        kind,
+        status,
+      },
+    };
+    checks.push(check);
+  }
+  SignatureReport {
+    hash,
+    threshold,
+    checks,
+    unsigned_owners: owners
+      .iter()
+      .filter(|o| !signed.contains(o))
+      .cloned()
+      .collect(),
+  }
+}
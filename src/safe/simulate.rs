@@ -0,0 +1,131 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dry runs of Safe transactions in the Safe's own context, through
+//! `StorageAccessible.simulateAndRevert` and the canonical `SimulateTxAccessor`.
+//! Nothing is signed and nothing is sent.
+
+use super::{SafeClient, SafeTransaction};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  web3::{
+    ethabi::{decode, param_type::ParamType, Token},
+    types::{Bytes as Web3Bytes, CallRequest},
+  },
+};
+
+/// What executing a SafeTransaction's call would do right now
+#[derive(Clone, Debug, PartialEq)]
+pub struct Simulation {
+  pub success: bool,
+  /// Gas used by the inner call, as measured around `execute`
+  pub gas_used: U256,
+  pub return_data: Vec<u8>,
+}
+
+impl Simulation {
+  /// The revert message of a failed call, e.g. "T02: Invalid ETH value"
+  pub fn revert_reason(&self) -> Option<String> {
+    if self.success {
+      return None;
+    }
+    utils::revert_reason(&self.return_data)
+  }
+}
+
+/// `SimulateTxAccessor.simulate` calldata for `tx`
+pub fn simulate_data(tx: &SafeTransaction) -> Vec<u8> {
+  utils::encode_call(
+    "simulate",
+    &[
+      ParamType::Address,
+      ParamType::Uint(256),
+      ParamType::Bytes,
+      ParamType::Uint(8),
+    ],
+    &[
+      Token::Address(tx.to),
+      Token::Uint(tx.value),
+      Token::Bytes(tx.data.clone()),
+      Token::Uint((tx.operation as u8).into()),
+    ],
+  )
+}
+
+/// Decode the `simulateAndRevert` revert payload:
+/// `success (32) || returndatasize (32) || returndata`
+pub fn decode_simulation(payload: &[u8]) -> Result<Simulation> {
+  if payload.len() < 64 {
+    return Err(Error::Other(format!(
+      "simulation payload too short: {} bytes",
+      payload.len()
+    )));
+  }
+  let length = U256::from_big_endian(&payload[32..64]);
+  if length > U256::from(payload.len() - 64) {
+    return Err(Error::Other("simulation payload truncated".to_string()));
+  }
+  let returndata = &payload[64..64 + length.as_usize()];
+  if U256::from_big_endian(&payload[0..32]).is_zero() {
+    // the accessor itself reverted, e.g. because it was not delegatecalled
+    return Err(Error::Other(format!(
+      "simulation failed: {}",
+      utils::revert_reason(returndata).unwrap_or_else(|| hex::encode(returndata))
+    )));
+  }
+  let tokens = decode(
+    &[ParamType::Uint(256), ParamType::Bool, ParamType::Bytes],
+    returndata,
+  )
+  .map_err(|e| Error::Other(format!("couldnt decode simulation: {}", e)))?;
+  match tokens.as_slice() {
+    [Token::Uint(gas_used), Token::Bool(success), Token::Bytes(return_data)] => Ok(Simulation {
+      success: *success,
+      gas_used: *gas_used,
+      return_data: return_data.clone(),
+    }),
+    _ => Err(Error::Other("couldnt decode simulation".to_string())),
+  }
+}
+
+impl SafeClient {
+  /// Run `tx`'s call as the Safe would, through the accessor set with
+  /// `set_simulate_accessor`. Signers can use this to refuse transactions that would fail.
+  pub async fn simulate(&self, tx: &SafeTransaction) -> Result<Simulation> {
+    let accessor = self.simulate_accessor.ok_or_else(|| {
+      Error::Other("no SimulateTxAccessor set, see set_simulate_accessor".to_string())
+    })?;
+    let data = utils::encode_call(
+      "simulateAndRevert",
+      &[ParamType::Address, ParamType::Bytes],
+      &[Token::Address(accessor), Token::Bytes(simulate_data(tx))],
+    );
+    let call = CallRequest {
+      to: Some(self.address),
+      data: Some(Web3Bytes(data)),
+      ..Default::default()
+    };
+    match self.web3.eth().call(call, None).await {
+      // some nodes hand back the revert payload as the result
+      Ok(result) => decode_simulation(&result.0),
+      Err(e) => match utils::revert_data(&e) {
+        Some(payload) => decode_simulation(&payload),
+        None => Err(e.into()),
+      },
+    }
+  }
+}
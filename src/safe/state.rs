@@ -0,0 +1,179 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A consistent snapshot of everything that defines a Safe's custody setup.
+
+use super::{GnosisSafe, SafeClient, SENTINEL_ADDRESS};
+use crate::{error::Error, utils, Result};
+use ethcontract::{
+  prelude::*,
+  web3::{
+    ethabi::{decode, param_type::ParamType},
+    types::{BlockId, BlockNumber, Bytes as Web3Bytes, CallRequest, U64},
+  },
+};
+
+/// keccak256("guard_manager.guard.address")
+pub const GUARD_STORAGE_SLOT: [u8; 32] = [
+  0x4a, 0x20, 0x4f, 0x62, 0x0c, 0x8c, 0x5c, 0xcd, 0xca, 0x3f, 0xd5, 0x4d, 0x00, 0x3b, 0xad, 0xd8,
+  0x5b, 0xa5, 0x00, 0x43, 0x6a, 0x43, 0x1f, 0x0c, 0xbd, 0xa4, 0xf5, 0x58, 0xc9, 0x3c, 0x34, 0xc8,
+];
+
+/// keccak256("fallback_manager.handler.address")
+pub const FALLBACK_HANDLER_STORAGE_SLOT: [u8; 32] = [
+  0x6c, 0x9a, 0x6c, 0x4a, 0x39, 0x28, 0x4e, 0x37, 0xed, 0x1c, 0xf5, 0x3d, 0x33, 0x75, 0x77, 0xd1,
+  0x42, 0x12, 0xa4, 0x87, 0x0f, 0xb9, 0x76, 0xa4, 0x36, 0x6c, 0x69, 0x3b, 0x93, 0x99, 0x18, 0xd5,
+];
+
+/// Modules fetched per `getModulesPaginated` call
+const MODULES_PAGE_SIZE: u64 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SafeState {
+  pub address: Address,
+  pub block_number: u64,
+  /// The node's `eth_chainId`
+  pub chain_id: u64,
+  pub version: String,
+  /// The singleton the proxy delegates to (storage slot 0)
+  pub singleton: Address,
+  pub threshold: u64,
+  pub owners: Vec<Address>,
+  pub nonce: u64,
+  pub modules: Vec<Address>,
+  /// Zero if no guard is set
+  pub guard: Address,
+  /// Zero if no fallback handler is set
+  pub fallback_handler: Address,
+}
+
+impl SafeState {
+  pub fn has_guard(&self) -> bool {
+    self.guard != utils::zero_address()
+  }
+
+  pub fn has_fallback_handler(&self) -> bool {
+    self.fallback_handler != utils::zero_address()
+  }
+}
+
+impl SafeClient {
+  /// Snapshot the Safe's state at the latest block
+  pub async fn state(&self) -> Result<SafeState> {
+    let block_number = self.web3.eth().block_number().await?.as_u64();
+    self.state_at(block_number).await
+  }
+
+  /// Snapshot the Safe's state at `block_number`, with every read pinned to that block
+  pub async fn state_at(&self, block_number: u64) -> Result<SafeState> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let number = BlockNumber::Number(U64::from(block_number));
+    let block = BlockId::Number(number);
+
+    let threshold = safe.get_threshold().block(block).call().await?.as_u64();
+    let owners = safe.get_owners().block(block).call().await?;
+    let nonce = safe.nonce().block(block).call().await?.as_u64();
+    let modules = self.modules_at(block).await?;
+
+    // what the node says, not what was pinned for hashing
+    let chain_id = self.web3.eth().chain_id().await?.as_u64();
+    if let Some(pinned) = self.chain_id {
+      if pinned != chain_id {
+        return Err(Error::Other(format!(
+          "node is on chain {} but chain id {} is pinned",
+          chain_id, pinned
+        )));
+      }
+    }
+
+    Ok(SafeState {
+      address: self.address,
+      block_number,
+      chain_id,
+      version: self.version_at(block).await?,
+      singleton: self.storage_address(U256::zero(), number).await?,
+      threshold,
+      owners,
+      nonce,
+      modules,
+      guard: self
+        .storage_address(U256::from_big_endian(&GUARD_STORAGE_SLOT), number)
+        .await?,
+      fallback_handler: self
+        .storage_address(
+          U256::from_big_endian(&FALLBACK_HANDLER_STORAGE_SLOT),
+          number,
+        )
+        .await?,
+    })
+  }
+
+  /// The Safe's `VERSION` string
+  pub async fn version(&self) -> Result<String> {
+    self.version_at(BlockId::Number(BlockNumber::Latest)).await
+  }
+
+  async fn version_at(&self, block: BlockId) -> Result<String> {
+    let call = CallRequest {
+      to: Some(self.address),
+      data: Some(Web3Bytes(utils::encode_call("VERSION", &[], &[]))),
+      ..Default::default()
+    };
+    let result = self.web3.eth().call(call, Some(block)).await?;
+    let tokens = decode(&[ParamType::String], &result.0)
+      .map_err(|e| Error::Other(format!("couldnt decode VERSION: {}", e)))?;
+    tokens
+      .into_iter()
+      .next()
+      .and_then(|t| t.into_string())
+      .ok_or_else(|| Error::Other("couldnt decode VERSION".to_string()))
+  }
+
+  /// Every enabled module, in linked list order
+  pub(super) async fn modules_at(&self, block: BlockId) -> Result<Vec<Address>> {
+    let safe = GnosisSafe::at(&self.web3, self.address);
+    let mut modules = Vec::new();
+    let mut start = SENTINEL_ADDRESS;
+    loop {
+      let (page, next) = safe
+        .get_modules_paginated(start, MODULES_PAGE_SIZE.into())
+        .block(block)
+        .call()
+        .await?;
+      if next == SENTINEL_ADDRESS || next == utils::zero_address() {
+        modules.extend(page);
+        return Ok(modules);
+      }
+      // 1.3.0 returns the first module of the next page as `next`, and paginating
+      // from it would skip it, so continue from the last module returned instead
+      start = match page.last() {
+        Some(last) => *last,
+        None => return Ok(modules),
+      };
+      modules.extend(page);
+    }
+  }
+
+  /// An address stored at `slot` of the Safe's storage, as in `getStorageAt`
+  pub(super) async fn storage_address(&self, slot: U256, block: BlockNumber) -> Result<Address> {
+    let word = self
+      .web3
+      .eth()
+      .storage(self.address, slot, Some(block))
+      .await?;
+    Ok(H160::from_slice(&word.0[12..]))
+  }
+}
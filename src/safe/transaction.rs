@@ -0,0 +1,131 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{safe::eip712, utils};
+use ethcontract::prelude::*;
+
+/// `Enum.Operation` of the Safe contracts
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Operation {
+  #[default]
+  Call = 0,
+  DelegateCall = 1,
+}
+
+/// Every field of `GnosisSafe.execTransaction` (except the signatures) plus the nonce
+/// they are signed with. The same value is used to encode, hash, estimate and execute,
+/// so what the owners sign is always what gets executed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafeTransaction {
+  pub to: Address,
+  pub value: U256,
+  pub data: Vec<u8>,
+  pub operation: Operation,
+  pub safe_tx_gas: U256,
+  pub base_gas: U256,
+  pub gas_price: U256,
+  pub gas_token: Address,
+  pub refund_receiver: Address,
+  pub nonce: U256,
+}
+
+impl SafeTransaction {
+  /// A plain call to `to` with no value, no data and no refund
+  pub fn new(to: Address, nonce: u64) -> Self {
+    Self {
+      to,
+      value: U256::zero(),
+      data: Vec::new(),
+      operation: Operation::Call,
+      safe_tx_gas: U256::zero(),
+      base_gas: U256::zero(),
+      gas_price: U256::zero(),
+      gas_token: utils::zero_address(),
+      refund_receiver: utils::zero_address(),
+      nonce: nonce.into(),
+    }
+  }
+
+  pub fn value(mut self, value: U256) -> Self {
+    self.value = value;
+    self
+  }
+
+  pub fn data(mut self, data: Vec<u8>) -> Self {
+    self.data = data;
+    self
+  }
+
+  pub fn operation(mut self, operation: Operation) -> Self {
+    self.operation = operation;
+    self
+  }
+
+  pub fn safe_tx_gas(mut self, safe_tx_gas: U256) -> Self {
+    self.safe_tx_gas = safe_tx_gas;
+    self
+  }
+
+  pub fn base_gas(mut self, base_gas: U256) -> Self {
+    self.base_gas = base_gas;
+    self
+  }
+
+  pub fn gas_price(mut self, gas_price: U256) -> Self {
+    self.gas_price = gas_price;
+    self
+  }
+
+  pub fn gas_token(mut self, gas_token: Address) -> Self {
+    self.gas_token = gas_token;
+    self
+  }
+
+  pub fn refund_receiver(mut self, refund_receiver: Address) -> Self {
+    self.refund_receiver = refund_receiver;
+    self
+  }
+
+  pub fn nonce(mut self, nonce: u64) -> Self {
+    self.nonce = nonce.into();
+    self
+  }
+
+  pub fn struct_hash(&self) -> [u8; 32] {
+    eip712::safe_tx_struct_hash(
+      self.to,
+      self.value,
+      &self.data,
+      self.operation as u8,
+      self.safe_tx_gas,
+      self.base_gas,
+      self.gas_price,
+      self.gas_token,
+      self.refund_receiver,
+      self.nonce,
+    )
+  }
+
+  /// Same bytes as `GnosisSafe.encodeTransactionData` on the Safe at `safe`
+  pub fn encode(&self, chain_id: u64, safe: Address) -> Vec<u8> {
+    eip712::encode_transaction_data(eip712::domain_separator(chain_id, safe), self.struct_hash())
+  }
+
+  /// Same hash as `GnosisSafe.getTransactionHash` on the Safe at `safe`
+  pub fn hash(&self, chain_id: u64, safe: Address) -> [u8; 32] {
+    eip712::keccak256(&self.encode(chain_id, safe))
+  }
+}
@@ -0,0 +1,88 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The Safe releases tideth can drive, detected from the proxy's singleton and `VERSION`.
+//! 1.3.0 and 1.4.x share the EIP-712 domain and `SafeTx` type, so their hashes match.
+//! They differ in which event params are indexed and in `requiredTxGas`, removed in 1.4.0.
+
+use super::SafeClient;
+use crate::{error::Error, utils, Result};
+use ethcontract::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SafeVersion {
+  /// 1.3.0, the release vendored in `contracts/`
+  V1_3,
+  /// 1.4.0 and 1.4.1
+  V1_4,
+}
+
+impl SafeVersion {
+  /// Parse a `VERSION` string, rejecting releases whose encoding tideth doesn't implement
+  pub fn parse(version: &str) -> Result<Self> {
+    match version {
+      "1.3.0" => Ok(SafeVersion::V1_3),
+      "1.4.0" | "1.4.1" => Ok(SafeVersion::V1_4),
+      v => Err(Error::UnsupportedSafeVersion(v.to_string())),
+    }
+  }
+
+  /// Whether the singleton still has `requiredTxGas`
+  pub fn has_required_tx_gas(&self) -> bool {
+    *self == SafeVersion::V1_3
+  }
+}
+
+impl SafeClient {
+  /// The singleton the proxy delegates to, from storage slot 0 (what `masterCopy()` returns).
+  /// Errors if the Safe address is not a proxy.
+  pub async fn singleton(&self) -> Result<Address> {
+    let singleton = self
+      .storage_address(U256::zero(), BlockNumber::Latest)
+      .await?;
+    if singleton == utils::zero_address() {
+      return Err(Error::Other(format!(
+        "{:?} is not a safe proxy",
+        self.address
+      )));
+    }
+    Ok(singleton)
+  }
+
+  /// Read the singleton's `VERSION` through the proxy, failing on unsupported releases
+  pub async fn detect_version(&self) -> Result<SafeVersion> {
+    let singleton = self.singleton().await?;
+    let version = self.version().await.map_err(|e| {
+      Error::Other(format!(
+        "{:?} (singleton {:?}) has no VERSION: {}",
+        self.address, singleton, e
+      ))
+    })?;
+    SafeVersion::parse(&version)
+  }
+
+  /// The pinned version, or the detected one, cached until the address changes
+  pub async fn safe_version(&self) -> Result<SafeVersion> {
+    if let Some(v) = self
+      .safe_version
+      .or_else(|| self.detected_version.get().copied())
+    {
+      return Ok(v);
+    }
+    let version = self.detect_version().await?;
+    Ok(*self.detected_version.get_or_init(|| version))
+  }
+}
@@ -15,7 +15,13 @@
 // along with tideth.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::Result;
-use ethcontract::prelude::{Address, H160};
+use ethcontract::{
+  prelude::{Address, H160},
+  web3::{
+    ethabi::{decode, encode, short_signature, ParamType, Token},
+    Error as Web3Error,
+  },
+};
 use std::str::FromStr;
 
 pub fn zero_address() -> Address {
@@ -31,3 +37,38 @@ pub fn address_or_default(address: Option<&str>) -> Result<H160> {
     Ok(zero_address())
   }
 }
+
+/// ABI encode a call to `name(params)`, for contracts without generated bindings
+pub fn encode_call(name: &str, params: &[ParamType], tokens: &[Token]) -> Vec<u8> {
+  let mut data = short_signature(name, params).to_vec();
+  data.extend(encode(tokens));
+  data
+}
+
+/// The revert payload of a failed `eth_call`. Nodes put it in the JSON-RPC error's `data`,
+/// either as a hex string (geth) or nested in an object (older hardhat and ganache).
+pub fn revert_data(err: &Web3Error) -> Option<Vec<u8>> {
+  let mut data = match err {
+    Web3Error::Rpc(e) => e.data.as_ref()?,
+    _ => return None,
+  };
+  loop {
+    if let Some(s) = data.as_str() {
+      return hex::decode(s.trim_start_matches("0x")).ok();
+    }
+    data = data.get("data")?;
+  }
+}
+
+/// The message of a `revert("...")` / `require(.., "...")` payload
+pub fn revert_reason(data: &[u8]) -> Option<String> {
+  let selector = short_signature("Error", &[ParamType::String]);
+  if data.len() < 4 || data[0..4] != selector {
+    return None;
+  }
+  decode(&[ParamType::String], &data[4..])
+    .ok()?
+    .into_iter()
+    .next()?
+    .into_string()
+}
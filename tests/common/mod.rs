@@ -0,0 +1,69 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+// shared by several test binaries, each of which only uses some of these
+#![allow(dead_code)]
+
+use ethcontract::{transport::DynTransport, Account};
+use tideth::safe::{SafeClient, SafeTransaction};
+
+/// Sign `tx` with the executor's own approval and execute it; the executor is
+/// the only owner needed at threshold 1.
+pub async fn try_exec(
+  safe: &SafeClient,
+  executor: Account<DynTransport>,
+  tx: &SafeTransaction,
+) -> tideth::Result<()> {
+  let mut signatures = safe.signatures(tx).await?;
+  signatures.add_approved_hash(executor.address())?;
+  safe.exec_signed(executor, tx, &signatures, None).await?;
+  Ok(())
+}
+
+/// Like `try_exec`, but panics if the transaction cannot be executed.
+pub async fn exec(safe: &SafeClient, executor: Account<DynTransport>, tx: &SafeTransaction) {
+  try_exec(safe, executor, tx).await.expect("couldnt exec");
+}
+
+/// Approve the hash of `tx` on chain from `approver`, then execute it from
+/// `executor` with both approvals.
+pub async fn approve_and_exec(
+  safe: &SafeClient,
+  executor: Account<DynTransport>,
+  approver: Account<DynTransport>,
+  tx: &SafeTransaction,
+) {
+  let hash = safe
+    .safe_transaction_hash(tx)
+    .await
+    .expect("couldnt hash tx");
+  safe
+    .approve_hash(approver, hash)
+    .await
+    .expect("couldnt approve hash");
+  let mut signatures = safe.signatures(tx).await.expect("couldnt build set");
+  safe
+    .add_approvals(&mut signatures)
+    .await
+    .expect("couldnt add approvals");
+  signatures
+    .add_approved_hash(executor.address())
+    .expect("couldnt add executor");
+  safe
+    .exec_signed(executor, tx, &signatures, None)
+    .await
+    .expect("couldnt exec owner tx");
+}
@@ -0,0 +1,126 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, Account, Http, Web3, H160};
+use tideth::router::{replay_acceptlist, AcceptlistEntry, RouterClient};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let start = router.block_number().await.expect("no block number");
+
+  let (a, b, c) = (
+    H160::from_low_u64_be(0xa),
+    H160::from_low_u64_be(0xb),
+    H160::from_low_u64_be(0xc),
+  );
+  router
+    .add_token(zero_account.clone(), a)
+    .await
+    .expect("couldnt accept a");
+  router
+    .add_token(zero_account.clone(), b)
+    .await
+    .expect("couldnt accept b");
+  let before_removal = router.block_number().await.expect("no block number");
+  router
+    .remove_token(zero_account.clone(), a)
+    .await
+    .expect("couldnt remove a");
+  // removing an asset that was never accepted still emits Removed
+  router
+    .remove_token(zero_account.clone(), c)
+    .await
+    .expect("couldnt remove c");
+  let removed_at = router.block_number().await.expect("no block number");
+
+  let events = router
+    .get_acceptlist_events(Some(start))
+    .await
+    .expect("couldnt get acceptlist events");
+  assert_eq!(events.len(), 4);
+  assert_eq!(events[0].asset, a);
+  assert!(events[0].accepted);
+  assert_eq!(events[2].asset, a);
+  assert!(!events[2].accepted);
+  assert_eq!(replay_acceptlist(&events[..2]).len(), 2);
+
+  let acceptlist = router
+    .acceptlist_history(None)
+    .await
+    .expect("couldnt get acceptlist history");
+  assert_eq!(acceptlist.len(), 3);
+  assert_eq!(
+    acceptlist[0],
+    AcceptlistEntry {
+      asset: a,
+      accepted: false,
+      added_at: Some(start + 1),
+      removed_at: Some(before_removal + 1),
+    }
+  );
+  assert_eq!(
+    acceptlist[1],
+    AcceptlistEntry {
+      asset: b,
+      accepted: true,
+      added_at: Some(before_removal),
+      removed_at: None,
+    }
+  );
+  assert_eq!(
+    acceptlist[2],
+    AcceptlistEntry {
+      asset: c,
+      accepted: false,
+      added_at: None,
+      removed_at: Some(removed_at),
+    }
+  );
+  for entry in &acceptlist {
+    let on_chain = router
+      .is_accepted(entry.asset)
+      .await
+      .expect("couldnt call is_accepted");
+    assert_eq!(on_chain, entry.accepted);
+  }
+
+  // only b is still accepted
+  let accepted = router
+    .accepted_assets(None)
+    .await
+    .expect("couldnt get acceptlist");
+  assert_eq!(accepted, vec![acceptlist[1].clone()]);
+
+  // as of the block b was accepted in, a was still accepted
+  let earlier = router
+    .accepted_assets(Some(before_removal))
+    .await
+    .expect("couldnt get acceptlist");
+  assert_eq!(earlier.len(), 2);
+  assert!(earlier.iter().all(|e| e.accepted));
+}
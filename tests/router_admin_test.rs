@@ -0,0 +1,136 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3, H160,
+};
+use tideth::{
+  handover::{HandoverStep, RouterHandover},
+  router::{AdminCall, RouterAction, RouterClient, RouterOwner},
+  safe::SafeClient,
+  utils,
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+  let asset = H160::from_low_u64_be(0xa55e7);
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+
+  assert_ne!(
+    router.remove_token_data(asset).unwrap()[0..4],
+    router.accept_token_data(asset).unwrap()[0..4]
+  );
+
+  // while the deployer owns the router, calls are sent directly
+  assert_eq!(
+    router.owner_kind().await.expect("couldnt get owner kind"),
+    RouterOwner::Account(accounts[0])
+  );
+  let accept = RouterAction::AcceptToken(asset);
+  match router
+    .admin(zero_account.clone(), &accept)
+    .await
+    .expect("couldnt accept")
+  {
+    AdminCall::Sent(_) => {}
+    AdminCall::Proposed(_) => panic!("expected a direct call"),
+  }
+  assert!(router.is_accepted(asset).await.unwrap());
+  assert!(router.admin(one_account.clone(), &accept).await.is_err());
+  assert!(router.accept_token_tx(&safe, asset).await.is_err());
+
+  let step = RouterHandover::new(&router, &safe)
+    .run(Some(zero_account.clone()), zero_account.clone(), &[], None)
+    .await
+    .expect("couldnt hand over");
+  assert_eq!(step, HandoverStep::Done);
+
+  // once the safe owns it, calls become safe transactions
+  assert_eq!(
+    router.owner_kind().await.expect("couldnt get owner kind"),
+    RouterOwner::Safe(safe.address())
+  );
+  assert!(router
+    .add_token(zero_account.clone(), H160::from_low_u64_be(0xb))
+    .await
+    .is_err());
+  let remove = match router
+    .admin(zero_account.clone(), &RouterAction::RemoveToken(asset))
+    .await
+    .expect("couldnt propose")
+  {
+    AdminCall::Proposed(tx) => tx,
+    AdminCall::Sent(_) => panic!("expected a proposal"),
+  };
+  assert_eq!(remove.to, router.address());
+  assert_eq!(remove.data, router.remove_token_data(asset).unwrap());
+  exec(&safe, zero_account.clone(), &remove).await;
+  assert!(!router.is_accepted(asset).await.unwrap());
+
+  // an ETH withdrawal carries the amount from the safe
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+  let before = web3.eth().balance(accounts[5], None).await.unwrap();
+  let withdraw = router
+    .withdraw_tx(&safe, accounts[5], utils::zero_address(), 1000)
+    .await
+    .expect("couldnt build withdrawal");
+  assert_eq!(withdraw.value, U256::from(1000));
+  exec(&safe, zero_account.clone(), &withdraw).await;
+  let after = web3.eth().balance(accounts[5], None).await.unwrap();
+  assert_eq!(after - before, U256::from(1000));
+
+  let transfer = router
+    .transfer_ownership_tx(&safe, accounts[1])
+    .await
+    .expect("couldnt build transfer");
+  exec(&safe, zero_account.clone(), &transfer).await;
+  assert_eq!(router.pending_owner().await.unwrap(), accounts[1]);
+}
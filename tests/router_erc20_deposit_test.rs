@@ -0,0 +1,181 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, web3::types::U256, Account, Http, Web3};
+use tideth::{
+  erc20,
+  router::{ApproveMode, RouterClient},
+};
+
+ethcontract::contract!("artifacts/contracts/Tether.sol/Tether.json");
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let two_account = Account::Local(accounts[2], None);
+  let tidechain_account = vec![7u8; 32];
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let usdt = Tether::builder(&web3)
+    .from(zero_account.clone())
+    .deploy()
+    .await
+    .expect("couldnt deploy tether");
+  let asset = usdt.address();
+  let allowance = || erc20::allowance(&web3, asset, accounts[0], router.address());
+
+  // nothing is sent for an asset the router doesn't accept
+  assert!(router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::Approve,
+    )
+    .await
+    .is_err());
+  router
+    .add_token(zero_account.clone(), asset)
+    .await
+    .expect("couldnt accept");
+  // or without the balance
+  assert!(router
+    .deposit_erc20(
+      two_account,
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::Approve,
+    )
+    .await
+    .is_err());
+  // or without an allowance when approving isn't allowed
+  assert!(router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::Never,
+    )
+    .await
+    .is_err());
+
+  let receipt = router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::Approve,
+    )
+    .await
+    .expect("couldnt deposit");
+  assert_eq!(receipt.approvals.len(), 1);
+  assert!(receipt.gas_used > receipt.approvals[0].gas_used);
+  assert_eq!(allowance().await.unwrap(), U256::zero());
+  let deposits = router
+    .get_deposits_by_asset(asset, None)
+    .await
+    .expect("couldnt get deposits");
+  assert_eq!(deposits.len(), 1);
+  assert_eq!(deposits[0].tx_hash, receipt.tx_hash);
+  assert_eq!(deposits[0].amount, 1000);
+
+  // a short non-zero allowance is reset before the exact approve
+  erc20::approve(
+    &web3,
+    zero_account.clone(),
+    asset,
+    router.address(),
+    5.into(),
+  )
+  .await
+  .expect("couldnt approve");
+  let receipt = router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::ApproveAndReset,
+    )
+    .await
+    .expect("couldnt deposit");
+  assert_eq!(receipt.approvals.len(), 2);
+  assert_eq!(allowance().await.unwrap(), U256::zero());
+
+  // a larger allowance was set up by the caller, so it is used as is and kept
+  erc20::approve(
+    &web3,
+    zero_account.clone(),
+    asset,
+    router.address(),
+    5000.into(),
+  )
+  .await
+  .expect("couldnt approve");
+  let receipt = router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account.clone(),
+      asset,
+      1000.into(),
+      ApproveMode::ApproveAndReset,
+    )
+    .await
+    .expect("couldnt deposit");
+  assert!(receipt.approvals.is_empty());
+  assert_eq!(allowance().await.unwrap(), U256::from(4000));
+
+  // an existing allowance needs no approval at all
+  erc20::approve(
+    &web3,
+    zero_account.clone(),
+    asset,
+    router.address(),
+    100.into(),
+  )
+  .await
+  .expect("couldnt approve");
+  let receipt = router
+    .deposit_erc20(
+      zero_account.clone(),
+      tidechain_account,
+      asset,
+      100.into(),
+      ApproveMode::Never,
+    )
+    .await
+    .expect("couldnt deposit");
+  assert!(receipt.approvals.is_empty());
+  assert_eq!(
+    erc20::balance_of(&web3, asset, router.address())
+      .await
+      .unwrap(),
+    3100
+  );
+}
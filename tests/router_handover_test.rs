@@ -0,0 +1,140 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::signing::{SecretKey, SecretKeyRef},
+  Account, Http, Web3,
+};
+use std::str::FromStr;
+use tideth::{
+  handover::{HandoverStep, RouterHandover},
+  router::RouterClient,
+  safe::{sign_hash, SafeClient},
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let three_account = Account::Local(accounts[3], None);
+
+  // hardhat accounts[0] and accounts[1]
+  let key0 =
+    SecretKey::from_str("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+      .expect("couldnt parse key");
+  let key1 =
+    SecretKey::from_str("59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d")
+      .expect("couldnt parse key");
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  let handover = RouterHandover::new(&router, &safe);
+  assert_eq!(handover.step().await.unwrap(), HandoverStep::Propose);
+  // without the router owner nothing can happen yet
+  let step = handover
+    .run(None, three_account.clone(), &[], None)
+    .await
+    .expect("couldnt run");
+  assert_eq!(step, HandoverStep::Propose);
+  assert!(handover.propose(three_account.clone()).await.is_err());
+  assert!(handover.claim_transaction().await.is_err());
+
+  // proposing stops at the claim, a non-owner executor can't sign it
+  let step = handover
+    .run(Some(zero_account.clone()), three_account.clone(), &[], None)
+    .await
+    .expect("couldnt run");
+  assert_eq!(step, HandoverStep::Claim);
+  assert_eq!(
+    router.pending_owner().await.expect("no pending owner"),
+    safe.address()
+  );
+
+  // one owner signs, a signature over something else is ignored
+  let claim = handover
+    .claim_transaction()
+    .await
+    .expect("couldnt build claim");
+  let hash = safe
+    .safe_transaction_hash(&claim)
+    .await
+    .expect("couldnt hash");
+  let signature = sign_hash(SecretKeyRef::new(&key0), &hash).expect("couldnt sign");
+  let stale = sign_hash(SecretKeyRef::new(&key1), &[7u8; 32]).expect("couldnt sign");
+  let step = handover
+    .run(
+      None,
+      three_account.clone(),
+      &[stale.clone(), signature.clone()],
+      None,
+    )
+    .await
+    .expect("couldnt run");
+  assert_eq!(step, HandoverStep::Claim);
+
+  // after an interruption the same claim is rebuilt, and an on-chain approval completes it
+  assert_eq!(
+    handover
+      .claim_transaction()
+      .await
+      .expect("couldnt build claim"),
+    claim
+  );
+  safe
+    .approve_hash(Account::Local(accounts[2], None), hash)
+    .await
+    .expect("couldnt approve");
+  let step = handover
+    .run(None, three_account.clone(), &[stale, signature], None)
+    .await
+    .expect("couldnt run");
+  assert_eq!(step, HandoverStep::Done);
+  assert_eq!(router.owner().await.expect("no owner"), safe.address());
+  handover.verify().await.expect("safe doesnt own router");
+
+  // re-running a finished handover does nothing
+  let step = handover
+    .run(Some(zero_account.clone()), three_account, &[], None)
+    .await
+    .expect("couldnt run");
+  assert_eq!(step, HandoverStep::Done);
+  assert_eq!(
+    handover.propose(zero_account).await.unwrap(),
+    HandoverStep::Done
+  );
+}
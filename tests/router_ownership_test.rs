@@ -0,0 +1,120 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, Account, Http, Web3};
+use tideth::{
+  router::{OwnershipChange, OwnershipPeriod, RouterClient},
+  utils,
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let deployed_at = router.block_number().await.expect("no block number");
+
+  let status = router.handover_status().await.expect("couldnt get status");
+  assert_eq!(status.owner, accounts[0]);
+  assert!(!status.is_pending());
+  assert_eq!(status.proposed_by, None);
+
+  router
+    .transfer_ownership(zero_account.clone(), accounts[1])
+    .await
+    .expect("couldnt propose");
+  let status = router.handover_status().await.expect("couldnt get status");
+  assert!(status.is_pending());
+  assert_eq!(status.pending_owner, accounts[1]);
+  assert_eq!(status.proposed_by, Some(accounts[0]));
+  assert_eq!(status.proposed_at, Some(deployed_at + 1));
+
+  router
+    .claim_ownership(one_account.clone())
+    .await
+    .expect("couldnt claim");
+  let claimed_at = router.block_number().await.expect("no block number");
+  let status = router.handover_status().await.expect("couldnt get status");
+  assert_eq!(status.owner, accounts[1]);
+  assert_eq!(status.pending_owner, utils::zero_address());
+  assert!(!status.is_pending());
+
+  let events = router
+    .get_ownership_events(Some(deployed_at))
+    .await
+    .expect("couldnt get ownership events");
+  let changes: Vec<_> = events
+    .iter()
+    .map(|e| (e.change, e.old_owner, e.new_owner))
+    .collect();
+  assert_eq!(
+    changes,
+    vec![
+      (
+        OwnershipChange::Transferred,
+        utils::zero_address(),
+        accounts[0]
+      ),
+      (OwnershipChange::Proposed, accounts[0], accounts[1]),
+      (OwnershipChange::Transferred, accounts[0], accounts[1]),
+    ]
+  );
+
+  let history = router
+    .ownership_history()
+    .await
+    .expect("couldnt get history");
+  assert_eq!(
+    history,
+    vec![
+      OwnershipPeriod {
+        owner: accounts[0],
+        from_block: deployed_at,
+        until_block: Some(claimed_at),
+      },
+      OwnershipPeriod {
+        owner: accounts[1],
+        from_block: claimed_at,
+        until_block: None,
+      },
+    ]
+  );
+
+  // a second proposal replaces the first
+  router
+    .transfer_ownership(one_account.clone(), accounts[2])
+    .await
+    .expect("couldnt propose");
+  router
+    .transfer_ownership(one_account.clone(), accounts[3])
+    .await
+    .expect("couldnt propose");
+  let status = router.handover_status().await.expect("couldnt get status");
+  assert_eq!(status.pending_owner, accounts[3]);
+  assert_eq!(status.proposed_by, Some(accounts[1]));
+  assert_eq!(status.proposed_at, Some(claimed_at + 2));
+  assert_eq!(router.ownership_history().await.unwrap().len(), 2);
+}
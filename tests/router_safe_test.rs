@@ -23,7 +23,10 @@ use ethcontract::{
   Account, Http, Web3, H160,
 };
 use std::{convert::TryInto, str::FromStr};
-use tideth::{router::RouterClient, safe::SafeClient};
+use tideth::{
+  router::RouterClient,
+  safe::{SafeClient, SafeTransaction, SignatureStatus},
+};
 
 ethcontract::contract!("artifacts/contracts/RustCoin.sol/RustCoin.json");
 
@@ -130,7 +133,7 @@ async fn main() {
       .claim_ownership_data()
       .expect("could make claim_ownership_data");
     let tx_data = safe
-      .encode_data(router.address(), 0, claimdata.clone(), nonce, None)
+      .encode_data(router.address(), 0, claimdata.clone(), nonce)
       .await
       .expect("couldnt encode claim data");
 
@@ -162,12 +165,41 @@ async fn main() {
 
   // must increment nonce by one each EXEC
   let wtxdata = safe
-    .encode_data(router.address(), 0, withdrawaldata.clone(), nonce, None)
+    .encode_data(router.address(), 0, withdrawaldata.clone(), nonce)
     .await
     .expect("couldnt encode withdrawal data");
 
   let signatures = all_sigs(wtxdata.0, accounts.clone());
 
+  // the same signatures in the wrong order must be caught before broadcasting
+  let withdrawal_tx = SafeTransaction::new(router.address(), nonce).data(withdrawaldata.clone());
+  let report = safe
+    .verify_signatures(&withdrawal_tx, &signatures, None)
+    .await
+    .expect("couldnt verify signatures");
+  assert!(report.is_valid(), "signatures should be valid");
+  assert_eq!(report.missing(), 0);
+  let mut reversed = signatures[65..130].to_vec();
+  reversed.extend_from_slice(&signatures[0..65]);
+  let report = safe
+    .verify_signatures(&withdrawal_tx, &reversed, None)
+    .await
+    .expect("couldnt verify signatures");
+  assert!(!report.is_valid(), "reversed signatures should be invalid");
+  assert_eq!(report.checks[1].status, SignatureStatus::OutOfOrder);
+  safe.set_preflight(true);
+  assert!(safe
+    .exec_with_data(
+      zero_account.clone(),
+      router.address(),
+      withdrawaldata.clone(),
+      reversed,
+      0,
+      None,
+    )
+    .await
+    .is_err());
+
   safe
     .exec_with_data(
       zero_account,
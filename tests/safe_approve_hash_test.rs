@@ -0,0 +1,133 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::safe::{SafeClient, SignatureStatus, SignatureType};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  // fund the safe
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  let tx = safe
+    .new_transaction(accounts[4])
+    .await
+    .expect("couldnt build tx")
+    .value(1000_u64.into());
+  let hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+
+  // accounts[1] approves on chain instead of signing
+  assert!(!safe
+    .is_hash_approved(accounts[1], hash)
+    .await
+    .expect("couldnt query approvedHashes"));
+  safe
+    .approve_hash(one_account, hash)
+    .await
+    .expect("couldnt approve hash");
+  assert!(safe
+    .is_hash_approved(accounts[1], hash)
+    .await
+    .expect("couldnt query approvedHashes"));
+
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  let added = safe
+    .add_approvals(&mut signatures)
+    .await
+    .expect("couldnt add approvals");
+  assert_eq!(added, vec![accounts[1]]);
+  assert!(!signatures.is_complete());
+
+  // accounts[0] sends the execTransaction, so its own entry needs no approval
+  signatures
+    .add_approved_hash(accounts[0])
+    .expect("couldnt add executor");
+  assert!(signatures
+    .signatures()
+    .iter()
+    .all(|s| s.kind == SignatureType::ApprovedHash));
+  let packed = signatures.encode().expect("threshold not met");
+
+  // from anyone else the executor's entry is not approved
+  let report = safe
+    .verify_signatures(&tx, &packed, Some(accounts[2]))
+    .await
+    .expect("couldnt verify");
+  assert!(!report.is_valid());
+  assert!(report
+    .checks
+    .iter()
+    .any(|c| c.owner == Some(accounts[0]) && c.status == SignatureStatus::NotApproved));
+  let report = safe
+    .verify_signatures(&tx, &packed, Some(accounts[0]))
+    .await
+    .expect("couldnt verify");
+  assert!(report.is_valid());
+
+  let before = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  safe
+    .exec_transaction(zero_account, &tx, packed, None)
+    .await
+    .expect("couldnt exec approved tx");
+  let after = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(after, before + U256::from(1000));
+}
@@ -0,0 +1,154 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::safe::{eip712, SafeClient, SignatureStatus, SignatureType};
+
+ethcontract::contract!(
+  "artifacts/contracts/SignatureValidatorMock.sol/SignatureValidatorMock.json"
+);
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  // two contract owners and one EOA, threshold 2
+  let validator1 = SignatureValidatorMock::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy validator");
+  let validator2 = SignatureValidatorMock::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy validator");
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![validator1.address(), validator2.address(), accounts[1]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  let tx = safe
+    .new_transaction(accounts[4])
+    .await
+    .expect("couldnt build tx")
+    .value(1000_u64.into());
+  let tx_data = safe.encode_transaction(&tx).await.expect("couldnt encode");
+  let good = eip712::keccak256(&tx_data).to_vec();
+  let bad = vec![0u8; 32];
+
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  assert!(safe
+    .add_contract_signature(&tx, &mut signatures, validator1.address(), bad.clone())
+    .await
+    .is_err());
+  safe
+    .add_contract_signature(&tx, &mut signatures, validator1.address(), good.clone())
+    .await
+    .expect("validator1 should accept");
+  safe
+    .add_contract_signature(&tx, &mut signatures, validator2.address(), good.clone())
+    .await
+    .expect("validator2 should accept");
+  assert!(signatures
+    .signatures()
+    .iter()
+    .all(|s| s.kind == SignatureType::Contract));
+  let packed = signatures.encode().expect("threshold not met");
+  // two static parts followed by two (length, data) dynamic parts
+  assert_eq!(packed.len(), 2 * 65 + 2 * (32 + 32));
+
+  let report = safe
+    .verify_signatures(&tx, &packed, None)
+    .await
+    .expect("couldnt verify");
+  assert!(report.is_valid());
+  assert_eq!(report.checks.len(), 2);
+
+  // a contract signature its owner rejects is reported, not reverted on
+  let mut rejected = safe.signatures(&tx).await.expect("couldnt build set");
+  rejected
+    .add_contract_signature(validator1.address(), bad)
+    .expect("couldnt add");
+  rejected
+    .add_contract_signature(validator2.address(), good)
+    .expect("couldnt add");
+  let report = safe
+    .verify_signatures(&tx, &rejected.encode().expect("encode"), None)
+    .await
+    .expect("couldnt verify");
+  assert!(!report.is_valid());
+  assert!(report.checks.iter().any(
+    |c| c.owner == Some(validator1.address()) && c.status == SignatureStatus::ContractRejected
+  ));
+  // sending execTransaction only stands in for a v = 1 entry, never a contract signature
+  let report = safe
+    .verify_signatures(
+      &tx,
+      &rejected.encode().expect("encode"),
+      Some(validator1.address()),
+    )
+    .await
+    .expect("couldnt verify");
+  assert!(!report.is_valid());
+  assert!(report.checks.iter().any(
+    |c| c.owner == Some(validator1.address()) && c.status == SignatureStatus::ContractRejected
+  ));
+
+  let before = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  safe
+    .exec_transaction(zero_account, &tx, packed, None)
+    .await
+    .expect("couldnt exec with contract signatures");
+  let after = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(after, before + U256::from(1000));
+}
@@ -0,0 +1,107 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, web3::types::U256, Account, Http, Web3, H160};
+use std::str::FromStr;
+use tideth::{
+  safe::{SafeClient, SafeSetup},
+  utils,
+};
+
+ethcontract::contract!(
+  "artifacts/contracts/GnosisSafeProxyFactory.sol/GnosisSafeProxyFactory.json"
+);
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let three_account = Account::Local(accounts[3], None);
+
+  let factory = GnosisSafeProxyFactory::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy proxy factory");
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  let singleton = safe
+    .deploy_factory(zero_account.clone())
+    .await
+    .expect("couldnt deploy singleton");
+  let singleton = H160::from_str(singleton.as_str()).expect("couldnt parse singleton");
+
+  // bad configurations never reach the chain
+  assert!(SafeSetup::new(vec![accounts[0]], 2).validate().is_err());
+  assert!(SafeSetup::new(vec![accounts[0], accounts[0]], 1)
+    .validate()
+    .is_err());
+  assert!(SafeSetup::new(vec![utils::zero_address()], 1)
+    .validate()
+    .is_err());
+  assert!(safe
+    .deploy_and_setup(
+      zero_account.clone(),
+      factory.address(),
+      singleton,
+      &SafeSetup::new(vec![], 1),
+      U256::zero(),
+    )
+    .await
+    .is_err());
+
+  let setup = SafeSetup::new(vec![accounts[0], accounts[1], accounts[2]], 2);
+  let salt_nonce = U256::from(7);
+  let predicted = safe
+    .predict_setup_address(factory.address(), singleton, &setup, salt_nonce)
+    .expect("couldnt predict");
+  assert_ne!(
+    predicted,
+    safe
+      .predict_address(factory.address(), singleton, &[], salt_nonce)
+      .expect("couldnt predict")
+  );
+
+  safe
+    .deploy_and_setup(
+      zero_account.clone(),
+      factory.address(),
+      singleton,
+      &setup,
+      salt_nonce,
+    )
+    .await
+    .expect("couldnt deploy and setup");
+  assert_eq!(safe.address(), predicted);
+
+  let state = safe.state().await.expect("couldnt read state");
+  assert_eq!(state.singleton, singleton);
+  assert_eq!(state.owners, setup.owners);
+  assert_eq!(state.threshold, 2);
+  assert_eq!(state.nonce, 0);
+
+  // nobody can set it up again
+  assert!(safe
+    .setup(three_account, vec![accounts[3]], 1)
+    .await
+    .is_err());
+  assert_eq!(
+    safe.get_owners().await.expect("couldnt get owners"),
+    setup.owners
+  );
+}
@@ -0,0 +1,138 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::{
+    signing::{SecretKey, SecretKeyRef},
+    types::{TransactionRequest, U256},
+  },
+  Account, Http, Web3,
+};
+use std::str::FromStr;
+use tideth::safe::{
+  eth_sign_hash, recover, recover_eth_sign, sign_eth_sign, sign_hash, SafeClient, SignatureType,
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  // hardhat accounts[0] and accounts[1]
+  let key1 =
+    SecretKey::from_str("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+      .expect("couldnt parse key");
+  let key2 =
+    SecretKey::from_str("59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d")
+      .expect("couldnt parse key");
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  let tx = safe
+    .new_transaction(accounts[4])
+    .await
+    .expect("couldnt build tx")
+    .value(1000_u64.into());
+  let hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+
+  // accounts[0] signs the raw hash, accounts[1] only has personal_sign
+  let raw = sign_hash(SecretKeyRef::new(&key1), &hash).expect("couldnt sign");
+  assert_eq!(recover(&hash, &raw).expect("couldnt recover"), accounts[0]);
+  let eth_signed = sign_eth_sign(SecretKeyRef::new(&key2), &hash).expect("couldnt sign");
+  assert!(eth_signed[64] == 31 || eth_signed[64] == 32);
+  assert_eq!(
+    recover_eth_sign(&hash, &eth_signed).expect("couldnt recover"),
+    accounts[1]
+  );
+  // what a personal_sign wallet hands back, before the v adjustment
+  let personal_signed =
+    sign_hash(SecretKeyRef::new(&key2), &eth_sign_hash(&hash)).expect("couldnt sign");
+  assert!(recover(&hash, &personal_signed).expect("couldnt recover") != accounts[1]);
+
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  assert_eq!(
+    signatures
+      .add_eth_sign(&personal_signed)
+      .expect("couldnt add eth_sign signature"),
+    accounts[1]
+  );
+  signatures.add(&raw).expect("couldnt add signature");
+  let kinds: Vec<SignatureType> = signatures.signatures().iter().map(|s| s.kind).collect();
+  assert!(kinds.contains(&SignatureType::Ecdsa));
+  assert!(kinds.contains(&SignatureType::EthSign));
+  let packed = signatures.encode().expect("threshold not met");
+  assert_eq!(
+    &packed[..],
+    if accounts[0] < accounts[1] {
+      [raw, eth_signed].concat()
+    } else {
+      [eth_signed, raw].concat()
+    }
+  );
+
+  let report = safe
+    .verify_signatures(&tx, &packed, None)
+    .await
+    .expect("couldnt verify");
+  assert!(report.is_valid());
+
+  let before = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  safe
+    .exec_transaction(zero_account, &tx, packed, None)
+    .await
+    .expect("couldnt exec with eth_sign signature");
+  let after = web3
+    .eth()
+    .balance(accounts[4], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(after, before + U256::from(1000));
+}
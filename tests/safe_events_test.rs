@@ -0,0 +1,131 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::safe::{SafeClient, SafeEvent, SafeTransaction};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let start = web3
+    .eth()
+    .block_number()
+    .await
+    .expect("couldnt get block number")
+    .as_u64();
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(1000.into()),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+  safe
+    .approve_hash(one_account, [7u8; 32])
+    .await
+    .expect("couldnt approve hash");
+
+  let tx = safe
+    .add_owner_tx(accounts[2], 2)
+    .await
+    .expect("couldnt build add");
+  let add_hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+  exec(&safe, zero_account.clone(), &tx).await;
+
+  let events: Vec<SafeEvent> = safe
+    .get_events(Some(start))
+    .await
+    .expect("couldnt get events")
+    .into_iter()
+    .map(|e| e.event)
+    .collect();
+  assert_eq!(
+    events,
+    vec![
+      SafeEvent::SafeSetup {
+        initiator: accounts[0],
+        owners: vec![accounts[0], accounts[1]],
+        threshold: 1.into(),
+        initializer: Default::default(),
+        fallback_handler: Default::default(),
+      },
+      SafeEvent::SafeReceived {
+        sender: accounts[0],
+        value: 1000.into(),
+      },
+      SafeEvent::ApproveHash {
+        approved_hash: [7u8; 32],
+        owner: accounts[1],
+      },
+      SafeEvent::AddedOwner { owner: accounts[2] },
+      SafeEvent::ChangedThreshold {
+        threshold: 2.into()
+      },
+      SafeEvent::ExecutionSuccess {
+        tx_hash: add_hash,
+        payment: U256::zero(),
+      },
+    ]
+  );
+
+  // metadata is ordered and points at real transactions
+  let logs = safe
+    .get_events(Some(start))
+    .await
+    .expect("couldnt get events");
+  assert!(logs
+    .windows(2)
+    .all(|w| (w[0].block_number, w[0].log_index) < (w[1].block_number, w[1].log_index)));
+  assert!(logs.iter().all(|l| l.tx_hash.len() == 32));
+
+  let executions = safe
+    .get_execution_logs(Some(start))
+    .await
+    .expect("couldnt get execution logs");
+  assert_eq!(executions.len(), 1);
+  assert_eq!(executions[0].inner_tx_hash, add_hash.to_vec());
+  assert_eq!(executions[0].payment, U256::zero());
+  assert_eq!(executions[0].tx_hash, logs[5].tx_hash);
+}
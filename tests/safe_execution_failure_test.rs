@@ -0,0 +1,99 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::{
+  error::Error,
+  safe::{SafeClient, SafeEvent},
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(1000.into()),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  // with a safeTxGas set, a failing inner call does not revert execTransaction
+  let tx = safe
+    .new_transaction(accounts[7])
+    .await
+    .expect("couldnt build tx")
+    .value(U256::exp10(18))
+    .safe_tx_gas(50_000.into());
+  let hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  signatures
+    .add_approved_hash(accounts[0])
+    .expect("couldnt add executor");
+
+  let nonce = safe.nonce().await.expect("couldnt get nonce");
+  let txid = match safe
+    .exec_signed(zero_account.clone(), &tx, &signatures, None)
+    .await
+  {
+    Err(Error::SafeExecutionFailure(safe_tx_hash, txid)) => {
+      assert_eq!(safe_tx_hash.0, hash);
+      txid
+    }
+    Err(e) => panic!("expected an execution failure, got {:?}", e),
+    Ok(_) => panic!("failed safe transaction reported as success"),
+  };
+  // the nonce is gone all the same
+  assert_eq!(safe.nonce().await.expect("couldnt get nonce"), nonce + 1);
+
+  let events = safe.get_events(None).await.expect("couldnt get events");
+  assert!(events.iter().any(|e| e.tx_hash == txid.0.to_vec()
+    && e.event
+      == SafeEvent::ExecutionFailure {
+        tx_hash: hash,
+        payment: U256::zero(),
+      }));
+  assert!(safe
+    .get_execution_logs(None)
+    .await
+    .expect("couldnt get execution logs")
+    .is_empty());
+}
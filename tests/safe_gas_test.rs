@@ -0,0 +1,127 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::safe::{data_gas, required_gas_left, SafeClient};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  assert_eq!(data_gas(&[0, 1, 0, 2]), 40);
+  assert_eq!(required_gas_left(U256::zero()), U256::from(3000));
+  assert_eq!(required_gas_left(U256::from(630_000)), U256::from(640_500));
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  // nothing is signed yet
+  let tx = safe
+    .new_transaction(accounts[6])
+    .await
+    .expect("couldnt build tx")
+    .value(1000.into());
+  let required = safe
+    .required_tx_gas(&tx)
+    .await
+    .expect("couldnt get requiredTxGas");
+  assert!(required > U256::zero());
+  let estimate = safe
+    .estimate_safe_tx_gas(&tx)
+    .await
+    .expect("couldnt estimate");
+  assert!(estimate.safe_tx_gas > required);
+  assert!(estimate.base_gas > U256::from(21_000));
+  assert!(estimate.gas_limit > estimate.safe_tx_gas + estimate.base_gas);
+
+  // more than the safe holds cannot be estimated
+  let too_much = tx.clone().value(U256::exp10(18));
+  assert!(safe.required_tx_gas(&too_much).await.is_err());
+  assert!(safe.estimate_safe_tx_gas(&too_much).await.is_err());
+
+  // the estimate is part of what gets signed
+  let tx = estimate.apply(tx);
+  assert_eq!(tx.safe_tx_gas, estimate.safe_tx_gas);
+  let hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+  safe
+    .approve_hash(one_account, hash)
+    .await
+    .expect("couldnt approve hash");
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  safe
+    .add_approvals(&mut signatures)
+    .await
+    .expect("couldnt add approvals");
+  signatures
+    .add_approved_hash(accounts[0])
+    .expect("couldnt add executor");
+
+  let before = web3
+    .eth()
+    .balance(accounts[6], None)
+    .await
+    .expect("couldnt get balance");
+  let result = safe
+    .exec_signed(
+      zero_account,
+      &tx,
+      &signatures,
+      Some(estimate.gas_limit.as_u128()),
+    )
+    .await
+    .expect("couldnt exec with the estimated gas");
+  assert!(U256::from(result.gas_used) <= estimate.gas_limit);
+  let after = web3
+    .eth()
+    .balance(accounts[6], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(after, before + U256::from(1000));
+}
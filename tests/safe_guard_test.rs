@@ -0,0 +1,128 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::try_exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::{
+  safe::{SafeClient, GUARD_INTERFACE_ID},
+  utils,
+};
+
+ethcontract::contract!("artifacts/contracts/GuardMock.sol/GuardMock.json");
+ethcontract::contract!(
+  "artifacts/contracts/SignatureValidatorMock.sol/SignatureValidatorMock.json"
+);
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+  assert_eq!(
+    safe.get_guard().await.expect("couldnt get guard"),
+    utils::zero_address()
+  );
+
+  // the guard rejects anything sent to accounts[9]
+  let guard = GuardMock::builder(&web3, accounts[9])
+    .deploy()
+    .await
+    .expect("couldnt deploy guard");
+  let not_guard = SignatureValidatorMock::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy validator");
+  assert!(safe
+    .supports_interface(guard.address(), GUARD_INTERFACE_ID)
+    .await
+    .expect("couldnt query interface"));
+  assert!(safe.is_guard(guard.address()).await.expect("couldnt check"));
+  assert!(!safe
+    .is_guard(not_guard.address())
+    .await
+    .expect("couldnt check"));
+  assert!(!safe.is_guard(accounts[9]).await.expect("couldnt check"));
+  assert!(safe.set_guard_tx(not_guard.address()).await.is_err());
+  assert!(safe.set_guard_tx(accounts[9]).await.is_err());
+  assert!(safe.set_guard_tx(utils::zero_address()).await.is_err());
+
+  let tx = safe
+    .set_guard_tx(guard.address())
+    .await
+    .expect("couldnt build setGuard");
+  try_exec(&safe, zero_account.clone(), &tx)
+    .await
+    .expect("couldnt set guard");
+  assert_eq!(
+    safe.get_guard().await.expect("couldnt get guard"),
+    guard.address()
+  );
+  let state = safe.state().await.expect("couldnt read state");
+  assert!(state.has_guard());
+
+  let tx = safe
+    .new_transaction(accounts[9])
+    .await
+    .expect("couldnt build tx")
+    .value(1000.into());
+  assert!(try_exec(&safe, zero_account.clone(), &tx).await.is_err());
+
+  let tx = safe.clear_guard_tx().await.expect("couldnt build clear");
+  try_exec(&safe, zero_account.clone(), &tx)
+    .await
+    .expect("couldnt clear guard");
+  assert_eq!(
+    safe.get_guard().await.expect("couldnt get guard"),
+    utils::zero_address()
+  );
+  let tx = safe
+    .new_transaction(accounts[9])
+    .await
+    .expect("couldnt build tx")
+    .value(1000.into());
+  try_exec(&safe, zero_account, &tx)
+    .await
+    .expect("couldnt pay out without the guard");
+}
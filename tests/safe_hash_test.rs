@@ -0,0 +1,144 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, Account, Address, Bytes, Http, Web3, U256};
+use tideth::safe::{eip712, Operation, SafeClient, SafeTransaction};
+
+ethcontract::contract!("artifacts/contracts/GnosisSafe.sol/GnosisSafe.json");
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account, vec![accounts[0], accounts[1], accounts[2]], 2)
+    .await
+    .expect("Couldnt setup");
+
+  let onchain = GnosisSafe::at(&web3, safe.address());
+
+  // domain separator
+  let chain_id = safe.chain_id().await.expect("couldnt get chain id");
+  let onchain_domain = onchain
+    .domain_separator()
+    .call()
+    .await
+    .expect("couldnt call domainSeparator");
+  assert_eq!(
+    eip712::domain_separator(chain_id, safe.address()),
+    onchain_domain.0
+  );
+
+  // the plain transactions SafeClient builds
+  let data = hex::decode("a9059cbb000000000000000000000000f39fd6e51aad88f6f4ce6ab8827279cfffb922660000000000000000000000000000000000000000000000000000000000000064")
+    .expect("couldnt decode data");
+  for (to, value, data, nonce) in vec![
+    (accounts[3], 0_u128, data.clone(), 0_u64),
+    (accounts[3], 1_000_000_u128, Vec::new(), 1),
+    (safe.address(), 0, data.clone(), 42),
+  ] {
+    let local = safe
+      .encode_data(to, value, data.clone(), nonce)
+      .await
+      .expect("couldnt encode locally");
+    let remote = onchain
+      .encode_transaction_data(
+        to,
+        value.into(),
+        Bytes(data.clone()),
+        0,
+        0_u64.into(),
+        0_u64.into(),
+        0_u64.into(),
+        Address::zero(),
+        Address::zero(),
+        nonce.into(),
+      )
+      .call()
+      .await
+      .expect("couldnt call encodeTransactionData");
+    assert_eq!(local.0, remote.0, "encodeTransactionData mismatch");
+
+    let local_hash = safe
+      .transaction_hash(to, value, data.clone(), nonce)
+      .await
+      .expect("couldnt hash locally");
+    let remote_hash = onchain
+      .get_transaction_hash(
+        to,
+        value.into(),
+        Bytes(data.clone()),
+        0,
+        0_u64.into(),
+        0_u64.into(),
+        0_u64.into(),
+        Address::zero(),
+        Address::zero(),
+        nonce.into(),
+      )
+      .call()
+      .await
+      .expect("couldnt call getTransactionHash");
+    assert_eq!(local_hash, remote_hash.0, "getTransactionHash mismatch");
+  }
+
+  // every field of the SafeTx type set
+  let tx = SafeTransaction::new(accounts[4], 0)
+    .value(U256::exp10(18))
+    .data(data.clone())
+    .operation(Operation::DelegateCall)
+    .safe_tx_gas(50_000_u64.into())
+    .base_gas(21_000_u64.into())
+    .gas_price(1_000_000_000_u64.into())
+    .gas_token(accounts[5])
+    .refund_receiver(accounts[6]);
+  let tx = SafeTransaction {
+    nonce: U256::MAX,
+    ..tx
+  };
+  let local = safe
+    .encode_transaction(&tx)
+    .await
+    .expect("couldnt encode locally");
+  let remote = onchain
+    .encode_transaction_data(
+      accounts[4],
+      U256::exp10(18),
+      Bytes(data),
+      1,
+      50_000_u64.into(),
+      21_000_u64.into(),
+      1_000_000_000_u64.into(),
+      accounts[5],
+      accounts[6],
+      U256::MAX,
+    )
+    .call()
+    .await
+    .expect("couldnt call encodeTransactionData");
+  assert_eq!(local, remote.0, "encodeTransactionData mismatch");
+  println!("local SafeTx encoding matches the contract");
+}
@@ -0,0 +1,186 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{
+  web3::types::{Bytes as Web3Bytes, Log},
+  Address, H256, U256,
+};
+use tideth::safe::{
+  decode_log, eip712::keccak256, execution_result, proxy_created, SafeEvent,
+  EXECUTION_FAILURE_TOPIC, EXECUTION_SUCCESS_TOPIC, PROXY_CREATION_TOPIC,
+};
+
+fn log(address: Address, topics: Vec<H256>, data: Vec<u8>) -> Log {
+  serde_json::from_value(serde_json::json!({
+    "address": address,
+    "topics": topics,
+    "data": Web3Bytes(data),
+  }))
+  .expect("couldnt build log")
+}
+
+fn topic(signature: &str) -> H256 {
+  H256(keccak256(signature.as_bytes()))
+}
+
+fn address_word(address: Address) -> H256 {
+  H256::from(address)
+}
+
+fn uint_word(value: u64) -> H256 {
+  let mut word = [0u8; 32];
+  U256::from(value).to_big_endian(&mut word);
+  H256(word)
+}
+
+fn concat(words: &[H256]) -> Vec<u8> {
+  words.iter().flat_map(|w| w.0.to_vec()).collect()
+}
+
+// the same event as 1.3.0 emits it, with nothing indexed, and as 1.4.x emits it,
+// with the first param indexed
+fn both_layouts(safe: Address, signature: &str, params: &[H256]) -> [Log; 2] {
+  [
+    log(safe, vec![topic(signature)], concat(params)),
+    log(
+      safe,
+      vec![topic(signature), params[0]],
+      concat(&params[1..]),
+    ),
+  ]
+}
+
+// no node needed: every log is built by hand
+const SAFE: u64 = 0x5afe;
+const OTHER: u64 = 0x0be5;
+
+#[test]
+fn execution_events() {
+  let safe = Address::from_low_u64_be(SAFE);
+  let other = Address::from_low_u64_be(OTHER);
+  let tx_hash = H256(keccak256(b"safe tx"));
+
+  assert_eq!(
+    topic("ExecutionSuccess(bytes32,uint256)").0,
+    EXECUTION_SUCCESS_TOPIC
+  );
+  assert_eq!(
+    topic("ExecutionFailure(bytes32,uint256)").0,
+    EXECUTION_FAILURE_TOPIC
+  );
+  // txHash is indexed from 1.4.0
+  for log in both_layouts(
+    safe,
+    "ExecutionSuccess(bytes32,uint256)",
+    &[tx_hash, uint_word(7)],
+  ) {
+    let expected = SafeEvent::ExecutionSuccess {
+      tx_hash: tx_hash.0,
+      payment: 7.into(),
+    };
+    assert_eq!(decode_log(&log), Some(expected.clone()));
+    assert_eq!(execution_result(safe, &[log.clone()]), Some(expected));
+    assert_eq!(execution_result(other, &[log]), None);
+  }
+  for log in both_layouts(
+    safe,
+    "ExecutionFailure(bytes32,uint256)",
+    &[tx_hash, uint_word(0)],
+  ) {
+    let expected = SafeEvent::ExecutionFailure {
+      tx_hash: tx_hash.0,
+      payment: U256::zero(),
+    };
+    assert_eq!(decode_log(&log), Some(expected.clone()));
+    assert_eq!(execution_result(safe, &[log]), Some(expected));
+  }
+}
+
+#[test]
+fn configuration_events() {
+  let safe = Address::from_low_u64_be(SAFE);
+  let account = Address::from_low_u64_be(0xacc);
+  let tx_hash = H256(keccak256(b"safe tx"));
+
+  // the owner, module, guard and approved hash are indexed from 1.4.0
+  for log in both_layouts(safe, "AddedOwner(address)", &[address_word(account)]) {
+    assert_eq!(
+      decode_log(&log),
+      Some(SafeEvent::AddedOwner { owner: account })
+    );
+  }
+  for log in both_layouts(safe, "EnabledModule(address)", &[address_word(account)]) {
+    assert_eq!(
+      decode_log(&log),
+      Some(SafeEvent::EnabledModule { module: account })
+    );
+  }
+  for log in both_layouts(safe, "ChangedGuard(address)", &[address_word(account)]) {
+    assert_eq!(
+      decode_log(&log),
+      Some(SafeEvent::ChangedGuard { guard: account })
+    );
+  }
+  for log in both_layouts(
+    safe,
+    "ApproveHash(bytes32,address)",
+    &[tx_hash, address_word(account)],
+  ) {
+    assert_eq!(
+      decode_log(&log),
+      Some(SafeEvent::ApproveHash {
+        approved_hash: tx_hash.0,
+        owner: account,
+      })
+    );
+  }
+
+  // unknown events and truncated data decode to nothing
+  assert_eq!(
+    decode_log(&log(safe, vec![topic("Unknown(address)")], vec![])),
+    None
+  );
+  assert_eq!(
+    decode_log(&log(
+      safe,
+      vec![topic("AddedOwner(address)")],
+      vec![0u8; 20]
+    )),
+    None
+  );
+}
+
+#[test]
+fn proxy_creation() {
+  let other = Address::from_low_u64_be(OTHER);
+  assert_eq!(
+    topic("ProxyCreation(address,address)").0,
+    PROXY_CREATION_TOPIC
+  );
+
+  // the factory indexes the proxy from 1.4.0
+  let factory = Address::from_low_u64_be(0xfac);
+  let proxy = Address::from_low_u64_be(0x9011);
+  let singleton = Address::from_low_u64_be(0x5119);
+  for log in both_layouts(
+    factory,
+    "ProxyCreation(address,address)",
+    &[address_word(proxy), address_word(singleton)],
+  ) {
+    assert_eq!(proxy_created(factory, &[log.clone()]), Some(proxy));
+    assert_eq!(proxy_created(other, &[log]), None);
+  }
+}
@@ -0,0 +1,146 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::{
+  error::Error,
+  safe::{prev_module, ModuleClient, SafeClient, SENTINEL_ADDRESS},
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+  assert!(safe
+    .get_modules()
+    .await
+    .expect("couldnt get modules")
+    .is_empty());
+
+  // more modules than fit in one page
+  let enabled = accounts[5..17].to_vec();
+  for module in enabled.iter() {
+    let tx = safe
+      .enable_module_tx(*module)
+      .await
+      .expect("couldnt build enable");
+    exec(&safe, zero_account.clone(), &tx).await;
+  }
+  assert!(safe.enable_module_tx(accounts[5]).await.is_err());
+  assert!(safe.enable_module_tx(SENTINEL_ADDRESS).await.is_err());
+
+  // enabling prepends to the linked list
+  let modules = safe.get_modules().await.expect("couldnt get modules");
+  let mut expected = enabled.clone();
+  expected.reverse();
+  assert_eq!(modules, expected);
+  assert_eq!(prev_module(&modules, modules[0]), Some(SENTINEL_ADDRESS));
+  assert_eq!(prev_module(&modules, modules[3]), Some(modules[2]));
+  let state = safe.state().await.expect("couldnt read state");
+  assert_eq!(state.modules, modules);
+
+  // accounts[5] moves ETH out of the safe without owner signatures
+  let module = ModuleClient::new(&web3, safe.address());
+  let before = web3
+    .eth()
+    .balance(accounts[19], None)
+    .await
+    .expect("couldnt get balance");
+  module
+    .exec_call(
+      Account::Local(accounts[5], None),
+      accounts[19],
+      1000.into(),
+      vec![],
+    )
+    .await
+    .expect("couldnt exec from module");
+  let after = web3
+    .eth()
+    .balance(accounts[19], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(after, before + U256::from(1000));
+
+  // a failing inner call is reported, and non-modules are refused
+  match module
+    .exec_call(
+      Account::Local(accounts[5], None),
+      accounts[19],
+      U256::exp10(18),
+      vec![],
+    )
+    .await
+  {
+    Err(Error::ModuleExecutionFailure(failed, _)) => assert_eq!(failed, accounts[5]),
+    Err(e) => panic!("expected ModuleExecutionFailure, got {:?}", e),
+    Ok(_) => panic!("the failing module call succeeded"),
+  }
+  assert!(module
+    .exec_call(
+      Account::Local(accounts[2], None),
+      accounts[19],
+      1.into(),
+      vec![]
+    )
+    .await
+    .is_err());
+
+  // disable one from the middle of the list
+  let tx = safe
+    .disable_module_tx(accounts[10])
+    .await
+    .expect("couldnt build disable");
+  exec(&safe, zero_account, &tx).await;
+  assert!(!safe
+    .is_module_enabled(accounts[10])
+    .await
+    .expect("couldnt check module"));
+  let modules = safe.get_modules().await.expect("couldnt get modules");
+  assert_eq!(modules.len(), enabled.len() - 1);
+  assert!(safe.disable_module_tx(accounts[10]).await.is_err());
+}
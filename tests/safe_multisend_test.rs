@@ -0,0 +1,155 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::{
+  router::RouterClient,
+  safe::{
+    decode_multi_send, decode_multi_send_data, encode_multi_send, MultiSendCall, Operation,
+    SafeClient,
+  },
+};
+
+ethcontract::contract!("artifacts/contracts/RustCoin.sol/RustCoin.json");
+ethcontract::contract!("artifacts/contracts/libraries/MultiSend.sol/MultiSend.json");
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let multi_send = MultiSend::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy multisend");
+  let erc20 = RustCoin::builder(&web3)
+    .deploy()
+    .await
+    .expect("Couldn't deploy the ERC20");
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  erc20
+    .transfer(router.address(), 100.into())
+    .send()
+    .await
+    .expect("Couldn't send the ERC20 to the router");
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[0],
+      to: Some(safe.address()),
+      value: Some(U256::exp10(17)),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt fund safe");
+
+  router
+    .transfer_ownership(zero_account.clone(), safe.address())
+    .await
+    .expect("Couldnt transfer router ownership to safe");
+  let claim = safe
+    .new_transaction(router.address())
+    .await
+    .expect("couldnt build claim")
+    .data(
+      router
+        .claim_ownership_data()
+        .expect("couldnt make claim data"),
+    );
+  exec(&safe, zero_account.clone(), &claim).await;
+  assert_eq!(
+    router.owner().await.expect("couldnt call owner"),
+    safe.address()
+  );
+
+  // three withdrawals, one nonce
+  let calls = vec![
+    router
+      .erc20_withdrawal_call(accounts[3], erc20.address(), 10)
+      .expect("couldnt build withdrawal"),
+    router
+      .erc20_withdrawal_call(accounts[4], erc20.address(), 20)
+      .expect("couldnt build withdrawal"),
+    router
+      .eth_withdrawal_call(accounts[5], 1000)
+      .expect("couldnt build withdrawal"),
+  ];
+  assert_eq!(
+    decode_multi_send(&encode_multi_send(&calls)).expect("couldnt decode packed"),
+    calls
+  );
+  assert!(decode_multi_send(&encode_multi_send(&calls)[..100]).is_err());
+
+  let tx = safe
+    .multi_send_tx(multi_send.address(), &calls)
+    .await
+    .expect("couldnt build batch");
+  assert_eq!(tx.operation, Operation::DelegateCall);
+  assert_eq!(
+    decode_multi_send_data(&tx.data).expect("couldnt decode batch"),
+    calls
+  );
+  assert!(safe
+    .multi_send_tx(multi_send.address(), &Vec::<MultiSendCall>::new())
+    .await
+    .is_err());
+
+  let nonce = safe.nonce().await.expect("couldnt get nonce");
+  let eth_before = web3
+    .eth()
+    .balance(accounts[5], None)
+    .await
+    .expect("couldnt get balance");
+  exec(&safe, zero_account, &tx).await;
+
+  assert_eq!(safe.nonce().await.expect("couldnt get nonce"), nonce + 1);
+  let balance = |a| erc20.balance_of(a).call();
+  assert_eq!(balance(accounts[3]).await.expect("balance"), 10.into());
+  assert_eq!(balance(accounts[4]).await.expect("balance"), 20.into());
+  assert_eq!(balance(router.address()).await.expect("balance"), 70.into());
+  let eth_after = web3
+    .eth()
+    .balance(accounts[5], None)
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(eth_after, eth_before + U256::from(1000));
+}
@@ -0,0 +1,110 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::approve_and_exec;
+use ethcontract::{transport::DynTransport, Account, Http, Web3};
+use tideth::safe::{prev_owner, SafeClient, SENTINEL_ADDRESS};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  let owners = safe.get_owners().await.expect("couldnt get owners");
+  assert_eq!(prev_owner(&owners, owners[0]), Some(SENTINEL_ADDRESS));
+  assert_eq!(prev_owner(&owners, owners[2]), Some(owners[1]));
+  assert_eq!(prev_owner(&owners, accounts[5]), None);
+
+  // invalid changes are rejected before anything is signed
+  assert!(safe.add_owner_tx(accounts[1], 2).await.is_err());
+  assert!(safe.add_owner_tx(accounts[3], 5).await.is_err());
+  assert!(safe.remove_owner_tx(accounts[5], 1).await.is_err());
+  assert!(safe.remove_owner_tx(accounts[2], 3).await.is_err());
+  assert!(safe.change_threshold_tx(0).await.is_err());
+  assert!(safe.swap_owner_tx(accounts[2], accounts[1]).await.is_err());
+
+  // add accounts[3] and keep the threshold at 2
+  let tx = safe
+    .add_owner_tx(accounts[3], 2)
+    .await
+    .expect("couldnt build add");
+  approve_and_exec(&safe, zero_account.clone(), one_account.clone(), &tx).await;
+  let owners = safe.get_owners().await.expect("couldnt get owners");
+  assert_eq!(owners.len(), 4);
+  assert!(owners.contains(&accounts[3]));
+
+  // a set collected for another transaction is refused
+  let stale = safe.signatures(&tx).await.expect("couldnt build set");
+  let next = safe
+    .change_threshold_tx(3)
+    .await
+    .expect("couldnt build threshold change");
+  assert!(safe
+    .exec_signed(zero_account.clone(), &next, &stale, None)
+    .await
+    .is_err());
+
+  // swap accounts[2] for accounts[4]
+  let tx = safe
+    .swap_owner_tx(accounts[2], accounts[4])
+    .await
+    .expect("couldnt build swap");
+  approve_and_exec(&safe, zero_account.clone(), one_account.clone(), &tx).await;
+  let owners = safe.get_owners().await.expect("couldnt get owners");
+  assert!(!owners.contains(&accounts[2]));
+  assert!(owners.contains(&accounts[4]));
+
+  // remove accounts[3] and keep the threshold at 2
+  let tx = safe
+    .remove_owner_tx(accounts[3], 2)
+    .await
+    .expect("couldnt build remove");
+  approve_and_exec(&safe, zero_account.clone(), one_account.clone(), &tx).await;
+  let owners = safe.get_owners().await.expect("couldnt get owners");
+  assert_eq!(owners.len(), 3);
+  assert!(!owners.contains(&accounts[3]));
+
+  let tx = safe
+    .change_threshold_tx(3)
+    .await
+    .expect("couldnt build threshold change");
+  approve_and_exec(&safe, zero_account, one_account, &tx).await;
+  assert_eq!(
+    safe.get_threshold().await.expect("couldnt get threshold"),
+    3
+  );
+}
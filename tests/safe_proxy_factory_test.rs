@@ -0,0 +1,131 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, web3::types::U256, Account, Http, Web3, H160};
+use std::str::FromStr;
+use tideth::safe::{
+  bundled_proxy_creation_code, predict_proxy_address, predict_proxy_address_with_code, SafeClient,
+};
+
+ethcontract::contract!(
+  "artifacts/contracts/GnosisSafeProxyFactory.sol/GnosisSafeProxyFactory.json"
+);
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let factory = GnosisSafeProxyFactory::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy proxy factory");
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  let singleton = safe
+    .deploy_factory(zero_account.clone())
+    .await
+    .expect("couldnt deploy singleton");
+  let singleton = H160::from_str(singleton.as_str()).expect("couldnt parse singleton");
+
+  // predicted offline, then checked against the factory's own creation code
+  let salt_nonce = U256::from(42);
+  let predicted =
+    predict_proxy_address(factory.address(), singleton, &[], salt_nonce).expect("couldnt predict");
+  assert_eq!(
+    safe
+      .predict_address(factory.address(), singleton, &[], salt_nonce)
+      .expect("couldnt predict"),
+    predicted
+  );
+  safe
+    .check_proxy_creation_code(factory.address())
+    .await
+    .expect("factory creates a different proxy");
+  let code = safe
+    .proxy_creation_code(factory.address())
+    .await
+    .expect("couldnt get creation code");
+  assert_eq!(
+    code,
+    bundled_proxy_creation_code().expect("no bundled code")
+  );
+  assert_eq!(
+    predict_proxy_address_with_code(factory.address(), &code, singleton, &[], salt_nonce),
+    predicted
+  );
+  // the salt nonce and the initializer both change the address
+  assert_ne!(
+    predict_proxy_address(factory.address(), singleton, &[], 43.into()).expect("couldnt predict"),
+    predicted
+  );
+  assert_ne!(
+    predict_proxy_address(factory.address(), singleton, &[1], salt_nonce).expect("couldnt predict"),
+    predicted
+  );
+  // an account is no factory
+  assert!(safe.check_proxy_creation_code(accounts[3]).await.is_err());
+
+  // nothing is deployed there yet
+  let before = web3
+    .eth()
+    .code(predicted, None)
+    .await
+    .expect("couldnt get code");
+  assert!(before.0.is_empty());
+
+  let address = safe
+    .deploy_with_proxy_factory(
+      zero_account.clone(),
+      factory.address(),
+      singleton,
+      vec![],
+      salt_nonce,
+    )
+    .await
+    .expect("couldnt create proxy");
+  assert_eq!(address, format!("{:?}", predicted));
+  assert_eq!(safe.address(), predicted);
+  let after = web3
+    .eth()
+    .code(predicted, None)
+    .await
+    .expect("couldnt get code");
+  assert!(!after.0.is_empty());
+
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  let state = safe.state().await.expect("couldnt read state");
+  assert_eq!(state.singleton, singleton);
+  assert_eq!(state.owners, vec![accounts[0], accounts[1]]);
+
+  // the same salt cannot be used twice
+  assert!(safe
+    .deploy_with_proxy_factory(
+      zero_account,
+      factory.address(),
+      singleton,
+      vec![],
+      salt_nonce
+    )
+    .await
+    .is_err());
+}
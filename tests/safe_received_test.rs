@@ -0,0 +1,120 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{
+  transport::DynTransport,
+  web3::types::{TransactionRequest, U256},
+  Account, Http, Web3,
+};
+use tideth::{router::RouterClient, safe::SafeClient, utils};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  router
+    .transfer_ownership(zero_account.clone(), safe.address())
+    .await
+    .expect("Couldnt transfer router ownership to safe");
+  let claim = safe
+    .new_transaction(router.address())
+    .await
+    .expect("couldnt build claim")
+    .data(
+      router
+        .claim_ownership_data()
+        .expect("couldnt make claim data"),
+    );
+  exec(&safe, zero_account.clone(), &claim).await;
+  let start = web3
+    .eth()
+    .block_number()
+    .await
+    .expect("couldnt get block number")
+    .as_u64();
+
+  // an ETH deposit through the router is forwarded to the safe
+  let tidechain_account = vec![9u8; 32];
+  let (deposit_tx, _) = router
+    .deposit(
+      Account::Local(accounts[4], None),
+      tidechain_account.clone(),
+      utils::zero_address(),
+      5000.into(),
+      Some(5000.into()),
+    )
+    .await
+    .expect("couldnt deposit");
+  // and ETH sent straight to the safe is not a deposit
+  web3
+    .eth()
+    .send_transaction(TransactionRequest {
+      from: accounts[4],
+      to: Some(safe.address()),
+      value: Some(700.into()),
+      ..Default::default()
+    })
+    .await
+    .expect("couldnt send eth");
+
+  let plain = safe
+    .get_received(Some(start))
+    .await
+    .expect("couldnt get received");
+  assert_eq!(plain.len(), 2);
+  assert!(plain.iter().all(|r| !r.is_router_deposit()));
+
+  let received = safe
+    .get_received_with_deposits(&router, Some(start))
+    .await
+    .expect("couldnt get received");
+  assert_eq!(received.len(), 2);
+
+  let via_router = &received[0];
+  assert_eq!(via_router.sender, router.address());
+  assert_eq!(via_router.value, U256::from(5000));
+  assert_eq!(via_router.tx_hash, deposit_tx);
+  let deposit = via_router.deposit.as_ref().expect("deposit not matched");
+  assert_eq!(deposit.account.to_vec(), tidechain_account);
+  assert_eq!(deposit.amount, 5000);
+
+  let direct = &received[1];
+  assert_eq!(direct.sender, accounts[4]);
+  assert_eq!(direct.value, U256::from(700));
+  assert!(!direct.is_router_deposit());
+}
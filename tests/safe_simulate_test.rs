@@ -0,0 +1,149 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{transport::DynTransport, web3::types::U256, Account, Http, Web3};
+use tideth::{
+  router::RouterClient,
+  safe::{change_threshold_data, SafeClient},
+};
+
+ethcontract::contract!("artifacts/contracts/RustCoin.sol/RustCoin.json");
+ethcontract::contract!(
+  "artifacts/contracts/accessors/SimulateTxAccessor.sol/SimulateTxAccessor.json"
+);
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  let accessor = SimulateTxAccessor::builder(&web3)
+    .deploy()
+    .await
+    .expect("couldnt deploy accessor");
+  let erc20 = RustCoin::builder(&web3)
+    .deploy()
+    .await
+    .expect("Couldn't deploy the ERC20");
+  let mut router = RouterClient::new(&web3, None).expect("derp");
+  router
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  erc20
+    .transfer(router.address(), 100.into())
+    .send()
+    .await
+    .expect("Couldn't send the ERC20 to the router");
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+  router
+    .transfer_ownership(zero_account.clone(), safe.address())
+    .await
+    .expect("Couldnt transfer router ownership to safe");
+  let claim = safe
+    .new_transaction(router.address())
+    .await
+    .expect("couldnt build claim")
+    .data(
+      router
+        .claim_ownership_data()
+        .expect("couldnt make claim data"),
+    );
+  exec(&safe, zero_account.clone(), &claim).await;
+  let nonce = safe.nonce().await.expect("couldnt get nonce");
+
+  // a withdrawal the router will accept
+  let withdrawal = safe
+    .new_transaction(router.address())
+    .await
+    .expect("couldnt build withdrawal")
+    .data(
+      router
+        .erc20_withdrawal_data(accounts[3], erc20.address(), 10)
+        .expect("couldnt build withdrawal data"),
+    );
+  assert!(safe.simulate(&withdrawal).await.is_err());
+  safe.set_simulate_accessor(accessor.address());
+  let simulation = safe.simulate(&withdrawal).await.expect("couldnt simulate");
+  assert!(simulation.success);
+  assert!(simulation.gas_used > U256::zero());
+  assert_eq!(simulation.revert_reason(), None);
+  // nothing actually moved
+  let balance = erc20
+    .balance_of(accounts[3])
+    .call()
+    .await
+    .expect("couldnt get balance");
+  assert_eq!(balance, U256::zero());
+
+  // an ETH withdrawal without the ETH attached
+  let withdrawal = safe
+    .new_transaction(router.address())
+    .await
+    .expect("couldnt build withdrawal")
+    .data(
+      router
+        .eth_withdrawal_data(accounts[3], 1000)
+        .expect("couldnt build withdrawal data"),
+    );
+  let simulation = safe.simulate(&withdrawal).await.expect("couldnt simulate");
+  assert!(!simulation.success);
+  assert_eq!(
+    simulation.revert_reason().as_deref(),
+    Some("T02: Invalid ETH value")
+  );
+
+  // owner changes run against the Safe itself
+  let add = safe
+    .add_owner_tx(accounts[2], 2)
+    .await
+    .expect("couldnt build add");
+  let simulation = safe.simulate(&add).await.expect("couldnt simulate");
+  assert!(simulation.success);
+  let bad_threshold = safe
+    .new_transaction(safe.address())
+    .await
+    .expect("couldnt build tx")
+    .data(change_threshold_data(5));
+  let simulation = safe
+    .simulate(&bad_threshold)
+    .await
+    .expect("couldnt simulate");
+  assert!(!simulation.success);
+  assert_eq!(simulation.revert_reason().as_deref(), Some("GS201"));
+
+  assert_eq!(safe.nonce().await.expect("couldnt get nonce"), nonce);
+  assert_eq!(
+    safe.get_owners().await.expect("couldnt get owners"),
+    vec![accounts[0], accounts[1]]
+  );
+}
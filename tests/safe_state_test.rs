@@ -0,0 +1,132 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+mod common;
+
+use common::exec;
+use ethcontract::{transport::DynTransport, Account, Http, Web3};
+use tideth::{safe::SafeClient, utils};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+  let one_account = Account::Local(accounts[1], None);
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  safe
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  safe
+    .setup(
+      zero_account.clone(),
+      vec![accounts[0], accounts[1], accounts[2]],
+      2,
+    )
+    .await
+    .expect("Couldnt setup");
+
+  let state = safe.state().await.expect("couldnt read state");
+  assert_eq!(state.address, safe.address());
+  assert_eq!(state.chain_id, 1337);
+  assert_eq!(state.version, "1.3.0");
+  assert_ne!(state.singleton, utils::zero_address());
+  assert_eq!(state.threshold, 2);
+  assert_eq!(state.owners, vec![accounts[0], accounts[1], accounts[2]]);
+  assert_eq!(state.nonce, 0);
+  assert!(state.modules.is_empty());
+  assert!(!state.has_guard());
+  assert!(!state.has_fallback_handler());
+
+  // raise the threshold, then read the earlier block again
+  let tx = safe
+    .change_threshold_tx(3)
+    .await
+    .expect("couldnt build threshold change");
+  let hash = safe
+    .safe_transaction_hash(&tx)
+    .await
+    .expect("couldnt hash tx");
+  safe
+    .approve_hash(one_account, hash)
+    .await
+    .expect("couldnt approve hash");
+  let mut signatures = safe.signatures(&tx).await.expect("couldnt build set");
+  safe
+    .add_approvals(&mut signatures)
+    .await
+    .expect("couldnt add approvals");
+  signatures
+    .add_approved_hash(accounts[0])
+    .expect("couldnt add executor");
+  safe
+    .exec_signed(zero_account.clone(), &tx, &signatures, None)
+    .await
+    .expect("couldnt exec threshold change");
+
+  let latest = safe.state().await.expect("couldnt read state");
+  assert_eq!(latest.threshold, 3);
+  assert_eq!(latest.nonce, 1);
+  assert!(latest.block_number > state.block_number);
+
+  let earlier = safe
+    .state_at(state.block_number)
+    .await
+    .expect("couldnt read earlier state");
+  assert_eq!(earlier, state);
+
+  // a snapshot reports the node's chain, and refuses a mismatched pin
+  let mut pinned = safe.clone();
+  pinned.set_chain_id(1337);
+  assert_eq!(
+    pinned.state().await.expect("couldnt read state").chain_id,
+    1337
+  );
+  pinned.set_chain_id(1);
+  assert!(pinned.state().await.is_err());
+
+  // more modules than fit in one getModulesPaginated page
+  let mut paged = SafeClient::new(&web3, None).expect("derp");
+  paged
+    .deploy(zero_account.clone())
+    .await
+    .expect("Didnt deploy");
+  paged
+    .setup(zero_account.clone(), vec![accounts[0]], 1)
+    .await
+    .expect("Couldnt setup");
+  let enabled = accounts[5..17].to_vec();
+  for module in enabled.iter() {
+    let tx = paged
+      .enable_module_tx(*module)
+      .await
+      .expect("couldnt build enable");
+    exec(&paged, zero_account.clone(), &tx).await;
+  }
+  let state = paged.state().await.expect("couldnt read state");
+  let mut expected = enabled;
+  expected.reverse();
+  assert_eq!(state.modules, expected);
+  assert_eq!(
+    paged.get_modules().await.expect("couldnt get modules"),
+    expected
+  );
+}
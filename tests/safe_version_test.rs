@@ -0,0 +1,132 @@
+// Copyright 2021-2022 Semantic Network Ltd.
+// This file is part of tideth.
+
+// tideth is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tidechain is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with tideth.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcontract::{transport::DynTransport, web3::types::U256, Account, Address, Http, Web3};
+use tideth::{
+  error::Error,
+  safe::{change_threshold_data, Operation, SafeClient, SafeEvent, SafeTransaction, SafeVersion},
+};
+
+#[tokio::test]
+async fn main() {
+  let web3 = Web3::new(DynTransport::new(
+    Http::new("http://localhost:8545").expect("couldnt setup web3"),
+  ));
+
+  let accounts = web3.eth().accounts().await.expect("getAccounts failed");
+  let zero_account = Account::Local(accounts[0], None);
+
+  assert_eq!(SafeVersion::parse("1.3.0").unwrap(), SafeVersion::V1_3);
+  assert_eq!(SafeVersion::parse("1.4.1").unwrap(), SafeVersion::V1_4);
+  assert!(matches!(
+    SafeVersion::parse("1.2.0"),
+    Err(Error::UnsupportedSafeVersion(_))
+  ));
+  assert!(SafeVersion::V1_3.has_required_tx_gas());
+  assert!(!SafeVersion::V1_4.has_required_tx_gas());
+
+  let mut safe = SafeClient::new(&web3, None).expect("derp");
+  let singleton: Address = safe
+    .deploy_factory(zero_account.clone())
+    .await
+    .expect("Didnt deploy singleton")
+    .parse()
+    .expect("couldnt parse singleton");
+  safe
+    .deploy_with_factory(zero_account.clone(), singleton)
+    .await
+    .expect("Didnt deploy proxy");
+  safe
+    .setup(zero_account.clone(), vec![accounts[0], accounts[1]], 1)
+    .await
+    .expect("Couldnt setup");
+
+  assert_eq!(safe.singleton().await.expect("no singleton"), singleton);
+  assert_eq!(
+    safe.detect_version().await.expect("couldnt detect"),
+    SafeVersion::V1_3
+  );
+  let tx = safe
+    .new_transaction(accounts[2])
+    .await
+    .expect("couldnt build tx");
+  let detected_hash = safe.safe_transaction_hash(&tx).await.expect("couldnt hash");
+
+  // the events decode whatever the layout
+  let events = safe.get_events(None).await.expect("couldnt get events");
+  match &events.first().expect("no events").event {
+    SafeEvent::SafeSetup {
+      owners, threshold, ..
+    } => {
+      assert_eq!(owners, &vec![accounts[0], accounts[1]]);
+      assert_eq!(*threshold, U256::one());
+    }
+    e => panic!("expected SafeSetup, got {:?}", e),
+  }
+
+  // the singleton itself isn't a proxy, and neither is an account
+  let bare = SafeClient::new(&web3, Some(&format!("{:?}", singleton))).expect("derp");
+  assert!(bare.detect_version().await.is_err());
+  let account = SafeClient::new(&web3, Some(&format!("{:?}", accounts[3]))).expect("derp");
+  assert!(account.detect_version().await.is_err());
+  // hashing needs no deployed Safe, as for a predicted address
+  assert_eq!(
+    account
+      .safe_transaction_hash(&tx)
+      .await
+      .expect("couldnt hash"),
+    tx.hash(1337, accounts[3])
+  );
+
+  // the detected version is cached per address, and dropped when it changes
+  let mut moved = safe.clone();
+  assert_eq!(
+    moved.safe_version().await.expect("couldnt detect"),
+    SafeVersion::V1_3
+  );
+  moved.set_address(accounts[3]);
+  assert!(moved.safe_version().await.is_err());
+
+  // a pinned version skips detection and hashes the same
+  let mut pinned = safe.clone();
+  pinned.set_safe_version(SafeVersion::V1_4);
+  pinned.set_chain_id(1337);
+  assert_eq!(
+    pinned
+      .safe_transaction_hash(&tx)
+      .await
+      .expect("couldnt hash"),
+    detected_hash
+  );
+
+  // without requiredTxGas the call is estimated from the Safe instead
+  let change =
+    SafeTransaction::new(safe.address(), tx.nonce.as_u64()).data(change_threshold_data(2));
+  let required = safe
+    .required_tx_gas(&change)
+    .await
+    .expect("couldnt get requiredTxGas");
+  let estimated = pinned
+    .required_tx_gas(&change)
+    .await
+    .expect("couldnt estimate");
+  assert!(!estimated.is_zero());
+  assert!(estimated < required + 10_000);
+  assert!(pinned
+    .required_tx_gas(&change.operation(Operation::DelegateCall))
+    .await
+    .is_err());
+}
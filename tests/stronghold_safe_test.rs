@@ -23,7 +23,10 @@ use iota_stronghold::{
   procedures::{self, KeyType},
   Client, KeyProvider, Location, Stronghold,
 };
-use tideth::{router::RouterClient, safe::SafeClient};
+use tideth::{
+  router::RouterClient,
+  safe::{SafeClient, SafeTransaction},
+};
 
 ethcontract::contract!("artifacts/contracts/RustCoin.sol/RustCoin.json");
 
@@ -130,7 +133,7 @@ async fn main() {
       .claim_ownership_data()
       .expect("could make claim_ownership_data");
     let tx_data = safe
-      .encode_data(router.address(), 0, claimdata.clone(), nonce, None)
+      .encode_data(router.address(), 0, claimdata.clone(), nonce)
       .await
       .expect("couldnt encode claim data");
 
@@ -192,7 +195,7 @@ async fn main() {
 
   // must increment nonce by one each EXEC
   let wtxdata = safe
-    .encode_data(router.address(), 0, withdrawaldata.clone(), nonce, None)
+    .encode_data(router.address(), 0, withdrawaldata.clone(), nonce)
     .await
     .expect("couldnt encode withdrawal data");
 
@@ -253,26 +256,52 @@ async fn main() {
     "initial accounts[3] balance {}",
     initial_accounts3_eth_balance.as_u128()
   );
+  let eth_tx = SafeTransaction::new(accounts[3], nonce).value(send_amt.into());
   let ethtxdata = safe
-    .encode_eth_tx(accounts[3], send_amt, nonce, None)
+    .encode_eth_tx(accounts[3], send_amt, nonce)
     .await
-    .expect("couldnt encode ETH withdrawal data");
-
-  let eth_signed1 = sign(&ethtxdata.0, strong1, loc1).await;
-  let eth_signed2 = sign(&ethtxdata.0, strong2, loc2).await;
+    .expect("couldnt encode ETH withdrawal data")
+    .0;
+  assert_eq!(
+    ethtxdata,
+    safe
+      .encode_transaction(&eth_tx)
+      .await
+      .expect("couldnt encode ETH withdrawal tx"),
+  );
 
-  let sorted_sigs = if address1 < address2 {
-    vec![eth_signed1, eth_signed2]
-  } else {
-    vec![eth_signed2, eth_signed1]
-  };
-  let mut eth_signatures = vec![];
-  for mut sig in sorted_sigs {
-    eth_signatures.append(&mut sig);
-  }
+  // signatures can be collected in any order, SafeSignatures sorts them by owner
+  let mut eth_signatures = safe
+    .signatures(&eth_tx)
+    .await
+    .expect("couldnt build signature set");
+  let signer2 = eth_signatures
+    .add(&sign(&ethtxdata, strong2, loc2).await)
+    .expect("invalid signature 2");
+  assert_eq!(signer2, address2);
+  assert!(
+    eth_signatures.encode().is_err(),
+    "threshold should not be met"
+  );
+  let signer1 = eth_signatures
+    .add(&sign(&ethtxdata, strong1.clone(), loc1.clone()).await)
+    .expect("invalid signature 1");
+  assert_eq!(signer1, address1);
+  assert!(
+    eth_signatures
+      .add(&sign(&ethtxdata, strong1, loc1).await)
+      .is_err(),
+    "duplicate signer should be rejected"
+  );
 
   safe
-    .exec_eth_tx(account1, accounts[3], send_amt, eth_signatures, None)
+    .exec_eth_tx(
+      account1,
+      accounts[3],
+      send_amt,
+      eth_signatures.encode().expect("threshold not met"),
+      None,
+    )
     .await
     .expect("Couldn't execute the ETH TX");
   println!("EXECUTED ETH WITHDRAWAL!");